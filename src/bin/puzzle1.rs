@@ -1,50 +1,637 @@
 use aoc2025::read_file;
+use std::{
+    fs::File,
+    io::{self, BufRead, BufWriter, Write},
+    thread,
+};
+
+/// Number of threads used to count the rotations. With a single thread, the rotations are
+/// streamed from the file instead of being read in memory.
+const THREADS: usize = 1;
+
+/// Set to true to print, for both methods, all the starting positions which produce the
+/// password of the puzzle.
+const FIND_STARTS: bool = false;
+
+/// Set to a file name to save the timeline of the dial as CSV.
+const TIMELINE_CSV: Option<&str> = None;
+
+/// Set to a file name to save the histogram of the dial positions as CSV.
+const HISTOGRAM_CSV: Option<&str> = None;
+
+/// Parse a rotation such as `L68` or `R14`, where leftward rotations are negative.
+fn try_parse_rotation(rot: &str) -> Result<i32, String> {
+    let steps = rot
+        .get(1..)
+        .and_then(|s| s.parse::<i32>().ok())
+        .filter(|&s| s >= 0);
+    match (rot.chars().next(), steps) {
+        (Some('L'), Some(steps)) => Ok(-steps),
+        (Some('R'), Some(steps)) => Ok(steps),
+        _ => Err(format!("invalid rotation {rot:?}")),
+    }
+}
 
 fn parse_rotation(rot: &str) -> i32 {
-    let steps: i32 = rot[1..].parse().unwrap();
-    if rot.starts_with("L") { -steps } else { steps }
+    try_parse_rotation(rot).unwrap()
+}
+
+/// A dial with `modulus` positions, numbered from `0`, which initially points to `start`.
+/// The positions in `targets` are those we are interested in, when the dial lands on them
+/// at the end of a rotation or passes through them during a rotation.
+#[derive(Debug, Clone)]
+struct Dial {
+    modulus: i32,
+    start: i32,
+    targets: Vec<i32>,
+}
+
+/// The result of applying a sequence of rotations to a dial, for each of its targets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct DialCounts {
+    /// Number of rotations which end on each target.
+    landings: Vec<u64>,
+    /// Number of times the dial points to each target, either during or at the end of a rotation.
+    crossings: Vec<u64>,
+}
+
+/// The two ways of computing the password from the counts of a dial.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Method {
+    /// Count the rotations which end on a target, as in part 1.
+    Landings,
+    /// Count every time the dial points to a target, as in part 2.
+    Crossings,
+}
+
+/// The result of searching for the starting positions which produce a given password.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct StartSearch {
+    /// Starting positions which produce the password, in increasing order.
+    matches: Vec<i32>,
+    /// For each starting position, the password it produces.
+    table: Vec<u64>,
+}
+
+/// A rotation of the dial, as recorded in a `Timeline`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct TimelineStep {
+    rotation: i32,
+    start: i32,
+    end: i32,
+    /// Number of times the dial points to a target during the rotation, final position included.
+    crossings: u64,
+    /// Number of crossings from the first rotation up to this one.
+    cumulative: u64,
+}
+
+/// The full history of a dial subject to a sequence of rotations.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Timeline {
+    steps: Vec<TimelineStep>,
+    /// For each position, number of rotations which end on it.
+    landed: Vec<u64>,
+    /// For each position, number of times the dial passes through it during a rotation,
+    /// without stopping there.
+    passed: Vec<u64>,
+}
+
+/// The effect of a sequence of rotations on a dial, for every possible starting position.
+/// Summaries of consecutive sequences may be combined, so that long sequences can be split
+/// in chunks and summarized in parallel.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Summary {
+    /// Net displacement of the dial, between `0` and the modulus.
+    displacement: i32,
+    /// `landings[k][p]` is the number of landings on the `k`-th target starting from `p`.
+    landings: Vec<Vec<u64>>,
+    /// `crossings[k][p]` is the number of crossings of the `k`-th target starting from `p`.
+    crossings: Vec<Vec<u64>>,
+}
+
+impl Summary {
+    /// Return the summary of the sequence made of the rotations summarized by `self`,
+    /// followed by those summarized by `next`. If `f1` and `f2` are the counts as a function
+    /// of the starting position, the combined counts are `f1(p) + f2((p + d1) mod m)`.
+    fn then(&self, next: &Summary) -> Summary {
+        let m = self.landings.first().map_or(1, |v| v.len());
+        let compose = |first: &Vec<Vec<u64>>, second: &Vec<Vec<u64>>| -> Vec<Vec<u64>> {
+            first
+                .iter()
+                .zip(second)
+                .map(|(f1, f2)| {
+                    (0..m)
+                        .map(|p| f1[p] + f2[(p + self.displacement as usize) % m])
+                        .collect()
+                })
+                .collect()
+        };
+        Summary {
+            displacement: ((self.displacement as usize + next.displacement as usize) % m) as i32,
+            landings: compose(&self.landings, &next.landings),
+            crossings: compose(&self.crossings, &next.crossings),
+        }
+    }
+
+    /// Return the counts for the dial starting from position `start`.
+    fn at(&self, start: i32) -> DialCounts {
+        DialCounts {
+            landings: self.landings.iter().map(|f| f[start as usize]).collect(),
+            crossings: self.crossings.iter().map(|f| f[start as usize]).collect(),
+        }
+    }
 }
 
-fn part1(rotations: &Vec<i32>) -> u32 {
-    let mut dial = 50;
-    let mut count_zeros = 0;
-    for rot in rotations {
-        dial = (dial + rot).rem_euclid(100);
-        // alternatively: dial = (dial + rot) % 100
-        count_zeros += (dial == 0) as u32;
+impl Timeline {
+    /// Write the steps of the timeline in CSV format, one rotation for each row.
+    fn write_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "rotation,start,end,crossings,cumulative")?;
+        for step in &self.steps {
+            writeln!(
+                out,
+                "{},{},{},{},{}",
+                step.rotation, step.start, step.end, step.crossings, step.cumulative
+            )?;
+        }
+        Ok(())
+    }
+
+    /// Write the histogram of the positions in CSV format, one position for each row.
+    fn write_histogram_csv<W: Write>(&self, mut out: W) -> io::Result<()> {
+        writeln!(out, "position,landed,passed")?;
+        for (pos, (landed, passed)) in self.landed.iter().zip(&self.passed).enumerate() {
+            writeln!(out, "{pos},{landed},{passed}")?;
+        }
+        Ok(())
     }
-    count_zeros
 }
 
-fn part2(rotations: &Vec<i32>) -> u32 {
-    let mut dial = 50;
-    let mut count_zeros = 0;
-    for rot in rotations {
-        let counts = if *rot >= 0 {
-            (dial + rot) / 100
+impl Dial {
+    /// Create a new dial, checking that the starting position and the targets are valid.
+    fn new(modulus: i32, start: i32, targets: Vec<i32>) -> Self {
+        assert!(modulus > 0, "the dial must have at least one position");
+        assert!(
+            (0..modulus).contains(&start),
+            "invalid starting position {start}"
+        );
+        for &t in &targets {
+            assert!((0..modulus).contains(&t), "invalid target {t}");
+        }
+        Self {
+            modulus,
+            start,
+            targets,
+        }
+    }
+
+    /// The dial used by the puzzle.
+    fn puzzle() -> Self {
+        Self::new(100, 50, vec![0])
+    }
+
+    /// Return the position of the dial after rotating by `rot` from position `pos`.
+    fn rotate(&self, pos: i32, rot: i32) -> i32 {
+        (pos as i64 + rot as i64).rem_euclid(self.modulus as i64) as i32
+    }
+
+    /// Return the number of times the dial points to `target` while rotating by `rot` from
+    /// position `pos`, including the final position but not the starting one. This is the number
+    /// of integers congruent to `target` in the interval `(pos, pos + rot]` for a rightward
+    /// rotation, or `[pos + rot, pos)` for a leftward one.
+    fn crossings_of(&self, pos: i32, rot: i32, target: i32) -> u64 {
+        let m = self.modulus as i64;
+        let (pos, rot, target) = (pos as i64, rot as i64, target as i64);
+        let count = if rot >= 0 {
+            (pos + rot - target).div_euclid(m) - (pos - target).div_euclid(m)
         } else {
-            let cross_zero = dial > 0 && dial + rot <= 0;
-            -(dial + rot) / 100 + cross_zero as i32
-            // alternatively: ((100 - dial).rem_euclid(100) - rot)/100
+            (pos - 1 - target).div_euclid(m) - (pos + rot - 1 - target).div_euclid(m)
         };
-        dial = (dial + rot).rem_euclid(100);
-        count_zeros += counts as u32;
+        count as u64
+    }
+
+    /// Apply the rotation `rot` to the dial at position `pos`, updating `counts`. Return the
+    /// new position of the dial.
+    fn step(&self, pos: i32, rot: i32, counts: &mut DialCounts) -> i32 {
+        for (k, &target) in self.targets.iter().enumerate() {
+            counts.crossings[k] += self.crossings_of(pos, rot, target);
+        }
+        let pos = self.rotate(pos, rot);
+        for (k, &target) in self.targets.iter().enumerate() {
+            counts.landings[k] += (pos == target) as u64;
+        }
+        pos
+    }
+
+    /// Return the counts before any rotation is applied.
+    fn zero_counts(&self) -> DialCounts {
+        DialCounts {
+            landings: vec![0; self.targets.len()],
+            crossings: vec![0; self.targets.len()],
+        }
+    }
+
+    /// Apply all the `rotations` to the dial, counting landings on and crossings of each target.
+    #[cfg(test)]
+    fn counts(&self, rotations: impl IntoIterator<Item = i32>) -> DialCounts {
+        let mut counts = self.zero_counts();
+        let mut pos = self.start;
+        for rot in rotations {
+            pos = self.step(pos, rot, &mut counts);
+        }
+        counts
+    }
+
+    /// Same as `counts`, but reading the rotations from `reader`, one for each line, in
+    /// constant memory. Empty lines are ignored, and invalid ones are reported as errors of
+    /// kind `InvalidData`.
+    fn counts_streaming<R: BufRead>(&self, mut reader: R) -> io::Result<DialCounts> {
+        let mut counts = self.zero_counts();
+        let mut pos = self.start;
+        let mut line = String::new();
+        while reader.read_line(&mut line)? > 0 {
+            let rot = line.trim();
+            if !rot.is_empty() {
+                let rot = try_parse_rotation(rot)
+                    .map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e))?;
+                pos = self.step(pos, rot, &mut counts);
+            }
+            line.clear();
+        }
+        Ok(counts)
+    }
+
+    /// Summarize the effect of `rotations` for every starting position, in time proportional
+    /// to the number of rotations plus the modulus, for each target.
+    ///
+    /// If the dial is at position `q = (p + s) mod m` and the rotation is `r = a * m + b`
+    /// rightward, a target `t` is crossed `a` times, plus once more if `(q - t) mod m >= m - b`.
+    /// This happens for `q` in a cyclic interval of length `b`, so that each rotation adds a
+    /// constant to all starting positions, and `1` to a cyclic interval of them, which is
+    /// recorded in a difference array. Leftward rotations are similar.
+    fn summary(&self, rotations: &[i32]) -> Summary {
+        let m = self.modulus as i64;
+        let n = self.targets.len();
+        let mut base = vec![0u64; n];
+        let mut diff = vec![vec![0i64; m as usize + 1]; n];
+        let mut landings = vec![vec![0u64; m as usize]; n];
+        // displacement of the dial before the current rotation
+        let mut s = 0;
+        for &rot in rotations {
+            let (a, b) = ((rot as i64).abs() / m, (rot as i64).abs() % m);
+            for (k, &t) in self.targets.iter().enumerate() {
+                base[k] += a as u64;
+                // first position q of the cyclic interval where there is an extra crossing
+                let q = if rot >= 0 { t as i64 - b } else { t as i64 + 1 };
+                let lo = (q - s).rem_euclid(m);
+                let hi = lo + b;
+                diff[k][lo as usize] += 1;
+                if hi <= m {
+                    diff[k][hi as usize] -= 1;
+                } else {
+                    diff[k][m as usize] -= 1;
+                    diff[k][0] += 1;
+                    diff[k][(hi - m) as usize] -= 1;
+                }
+            }
+            s = (s + rot as i64).rem_euclid(m);
+            for (k, &t) in self.targets.iter().enumerate() {
+                landings[k][(t as i64 - s).rem_euclid(m) as usize] += 1;
+            }
+        }
+        let crossings = diff
+            .iter()
+            .zip(&base)
+            .map(|(d, &c)| {
+                d[..m as usize]
+                    .iter()
+                    .scan(0, |acc, &x| {
+                        *acc += x;
+                        Some(c + *acc as u64)
+                    })
+                    .collect()
+            })
+            .collect();
+        Summary {
+            displacement: s as i32,
+            landings,
+            crossings,
+        }
     }
-    count_zeros
+
+    /// Same as `counts`, but splitting the rotations in `threads` chunks which are summarized
+    /// in parallel. The summaries are then combined sequentially. At most `MAX_THREADS`
+    /// threads are spawned, whatever the value of `threads`.
+    fn counts_parallel(&self, rotations: &[i32], threads: usize) -> DialCounts {
+        const MAX_THREADS: usize = 64;
+        let threads = threads.clamp(1, MAX_THREADS);
+        let chunk_size = rotations.len().div_ceil(threads).max(1);
+        let summaries: Vec<Summary> = thread::scope(|scope| {
+            let handles: Vec<_> = rotations
+                .chunks(chunk_size)
+                .map(|chunk| scope.spawn(|| self.summary(chunk)))
+                .collect();
+            handles.into_iter().map(|h| h.join().unwrap()).collect()
+        });
+        let total = summaries
+            .iter()
+            .fold(self.summary(&[]), |acc, summary| acc.then(summary));
+        total.at(self.start)
+    }
+
+    /// Return, for each starting position, the password obtained with `method`, summing the
+    /// counts of all targets. The starting position of the dial is ignored.
+    fn password_table(&self, rotations: &[i32], method: Method) -> Vec<u64> {
+        let summary = self.summary(rotations);
+        let counts = match method {
+            Method::Landings => &summary.landings,
+            Method::Crossings => &summary.crossings,
+        };
+        (0..self.modulus as usize)
+            .map(|p| counts.iter().map(|f| f[p]).sum())
+            .collect()
+    }
+
+    /// Find all the starting positions for which `rotations` produce `password` with `method`.
+    fn find_starts(&self, rotations: &[i32], method: Method, password: u64) -> StartSearch {
+        let table = self.password_table(rotations, method);
+        let matches = (0..self.modulus)
+            .filter(|&p| table[p as usize] == password)
+            .collect();
+        StartSearch { matches, table }
+    }
+
+    /// Apply all the `rotations` to the dial, recording every step and how often each position
+    /// is reached. This takes time proportional to the number of rotations plus the modulus.
+    ///
+    /// A rotation by `a * m + b` points `a` times to every position, plus once to each of the
+    /// `b` positions following the starting one in the direction of the rotation, which form a
+    /// cyclic interval recorded in a difference array, as in `summary`.
+    fn timeline(&self, rotations: &[i32]) -> Timeline {
+        let m = self.modulus as i64;
+        let mut steps = Vec::with_capacity(rotations.len());
+        let mut landed = vec![0; m as usize];
+        // number of full turns, and difference array of the partial ones
+        let mut turns = 0u64;
+        let mut diff = vec![0i64; m as usize + 1];
+        let mut cumulative = 0;
+        let mut pos = self.start;
+        for &rot in rotations {
+            let end = self.rotate(pos, rot);
+            let crossings = self
+                .targets
+                .iter()
+                .map(|&target| self.crossings_of(pos, rot, target))
+                .sum();
+            cumulative += crossings;
+            steps.push(TimelineStep {
+                rotation: rot,
+                start: pos,
+                end,
+                crossings,
+                cumulative,
+            });
+            let (a, b) = ((rot as i64).abs() / m, (rot as i64).abs() % m);
+            turns += a as u64;
+            let lo = if rot >= 0 {
+                pos as i64 + 1
+            } else {
+                pos as i64 - b
+            }
+            .rem_euclid(m);
+            let hi = lo + b;
+            diff[lo as usize] += 1;
+            if hi <= m {
+                diff[hi as usize] -= 1;
+            } else {
+                diff[m as usize] -= 1;
+                diff[0] += 1;
+                diff[(hi - m) as usize] -= 1;
+            }
+            // the final position is counted as landed, not passed
+            diff[end as usize] -= (rot != 0) as i64;
+            diff[end as usize + 1] += (rot != 0) as i64;
+            landed[end as usize] += 1;
+            pos = end;
+        }
+        let passed = diff[..m as usize]
+            .iter()
+            .scan(0, |acc, &x| {
+                *acc += x;
+                Some((turns as i64 + *acc) as u64)
+            })
+            .collect();
+        Timeline {
+            steps,
+            landed,
+            passed,
+        }
+    }
+
+    /// Return, for each target, the number of rotations which end on it.
+    #[cfg(test)]
+    fn landings(&self, rotations: &[i32]) -> Vec<u64> {
+        self.counts(rotations.iter().copied()).landings
+    }
+
+    /// Return, for each target, the number of times the dial points to it.
+    #[cfg(test)]
+    fn crossings(&self, rotations: &[i32]) -> Vec<u64> {
+        self.counts(rotations.iter().copied()).crossings
+    }
+}
+
+/// Return the password from the counts of the puzzle dial.
+fn part1(counts: &DialCounts) -> u64 {
+    counts.landings[0]
+}
+
+/// Return the password from the counts of the puzzle dial, with the new method.
+fn part2(counts: &DialCounts) -> u64 {
+    counts.crossings[0]
+}
+
+/// Save the timeline of the puzzle dial and the histogram of its positions in the CSV files
+/// `timeline_file` and `histogram_file`, when they are given.
+fn export_timeline(
+    rotations: &[i32],
+    timeline_file: Option<&str>,
+    histogram_file: Option<&str>,
+) -> io::Result<()> {
+    let timeline = Dial::puzzle().timeline(rotations);
+    if let Some(filename) = timeline_file {
+        timeline.write_csv(BufWriter::new(File::create(filename)?))?;
+    }
+    if let Some(filename) = histogram_file {
+        timeline.write_histogram_csv(BufWriter::new(File::create(filename)?))?;
+    }
+    Ok(())
 }
 
 #[test]
 fn test() {
     let rotations = read_file("inputs/puzzle1_example.txt", "\n", parse_rotation);
-    assert_eq!(part1(&rotations), 3);
-    assert_eq!(part2(&rotations), 6);
+    let counts = Dial::puzzle().counts(rotations);
+    assert_eq!(part1(&counts), 3);
+    assert_eq!(part2(&counts), 6);
     let rotations = read_file("inputs/puzzle1.txt", "\n", parse_rotation);
-    assert_eq!(part1(&rotations), 992);
-    assert_eq!(part2(&rotations), 6133);
+    let counts = Dial::puzzle().counts(rotations);
+    assert_eq!(part1(&counts), 992);
+    assert_eq!(part2(&counts), 6133);
 }
 
-fn main() {
+#[test]
+fn test_dial() {
+    // compare with a simulation which moves the dial one step at a time
+    let dial = Dial::new(7, 3, vec![0, 3, 6]);
+    let mut rng = aoc2025::rng::Lcg::new(12345);
+    let rotations: Vec<i32> = (0..1000).map(|_| rng.below(40) as i32 - 20).collect();
+    let mut expected = DialCounts {
+        landings: vec![0; 3],
+        crossings: vec![0; 3],
+    };
+    let mut pos = dial.start;
+    for &rot in &rotations {
+        for _ in 0..rot.abs() {
+            pos = (pos + rot.signum()).rem_euclid(dial.modulus);
+            if let Some(k) = dial.targets.iter().position(|&t| t == pos) {
+                expected.crossings[k] += 1;
+            }
+        }
+        if let Some(k) = dial.targets.iter().position(|&t| t == pos) {
+            expected.landings[k] += 1;
+        }
+    }
+    assert_eq!(dial.counts(rotations), expected);
+}
+
+#[test]
+fn test_streaming() {
+    let file = File::open("inputs/puzzle1.txt").unwrap();
+    let counts = Dial::puzzle()
+        .counts_streaming(io::BufReader::new(file))
+        .unwrap();
+    assert_eq!(counts.landings, vec![992]);
+    assert_eq!(counts.crossings, vec![6133]);
+    let counts = Dial::puzzle()
+        .counts_streaming("L50\r\nR250\r\n\r\nL1\n".as_bytes())
+        .unwrap();
+    assert_eq!(counts.landings, vec![1]);
+    assert_eq!(counts.crossings, vec![3]);
+    for input in ["L50\nX3\n", "R\n", "L-5\n", "R1x\n"] {
+        let error = Dial::puzzle()
+            .counts_streaming(input.as_bytes())
+            .unwrap_err();
+        assert_eq!(error.kind(), io::ErrorKind::InvalidData);
+    }
+}
+
+#[test]
+fn test_parallel() {
+    let dial = Dial::new(7, 3, vec![0, 3, 6]);
+    let rotations: Vec<i32> = (0..1000).map(|i| (i * 37 % 101) - 50).collect();
+    let expected = dial.counts(rotations.iter().copied());
+    for threads in [1, 2, 3, 8, 2000] {
+        assert_eq!(dial.counts_parallel(&rotations, threads), expected);
+    }
+    assert_eq!(dial.counts_parallel(&[], 4), dial.zero_counts());
     let rotations = read_file("inputs/puzzle1.txt", "\n", parse_rotation);
-    println!("Part 1: {}", part1(&rotations));
-    println!("Part 2: {}", part2(&rotations));
+    let counts = Dial::puzzle().counts_parallel(&rotations, 4);
+    assert_eq!(counts.landings, vec![992]);
+    assert_eq!(counts.crossings, vec![6133]);
+
+    // on a dial with a single position, every step points to the target
+    let counts = Dial::new(1, 0, vec![0]).counts_parallel(&[i32::MAX, -i32::MAX, i32::MAX], 2);
+    assert_eq!(part2(&counts), 3 * i32::MAX as u64);
+}
+
+#[test]
+fn test_find_starts() {
+    let rotations = read_file("inputs/puzzle1_example.txt", "\n", parse_rotation);
+    let search = Dial::puzzle().find_starts(&rotations, Method::Crossings, 6);
+    assert!(search.matches.contains(&50));
+    for start in 0..100 {
+        let dial = Dial::new(100, start, vec![0]);
+        assert_eq!(search.table[start as usize], dial.crossings(&rotations)[0]);
+        assert_eq!(
+            search.matches.contains(&start),
+            dial.crossings(&rotations)[0] == 6
+        );
+    }
+    let search = Dial::puzzle().find_starts(&rotations, Method::Landings, 3);
+    for start in 0..100 {
+        let dial = Dial::new(100, start, vec![0]);
+        assert_eq!(search.table[start as usize], dial.landings(&rotations)[0]);
+    }
+    assert!(search.matches.contains(&50));
+    assert!(!search.matches.contains(&0));
+}
+
+#[test]
+fn test_timeline() {
+    let rotations = read_file("inputs/puzzle1_example.txt", "\n", parse_rotation);
+    let timeline = Dial::puzzle().timeline(&rotations);
+    assert_eq!(timeline.steps.len(), rotations.len());
+    assert_eq!(timeline.steps.last().unwrap().cumulative, 6);
+    assert_eq!(timeline.landed[0], 3);
+    assert_eq!(timeline.landed.iter().sum::<u64>(), rotations.len() as u64);
+    assert_eq!(timeline.landed[0] + timeline.passed[0], 6);
+    let mut csv = Vec::new();
+    timeline.write_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    let mut lines = csv.lines();
+    assert_eq!(
+        lines.next(),
+        Some("rotation,start,end,crossings,cumulative")
+    );
+    assert_eq!(lines.next(), Some("-68,50,82,1,1"));
+    assert_eq!(lines.next(), Some("-30,82,52,0,1"));
+    let mut csv = Vec::new();
+    timeline.write_histogram_csv(&mut csv).unwrap();
+    let csv = String::from_utf8(csv).unwrap();
+    assert_eq!(csv.lines().nth(1), Some("0,3,3"));
+
+    // compare the histogram with the crossings of every position, one rotation at a time
+    let dial = Dial::new(7, 3, vec![0]);
+    let rotations: Vec<i32> = (0..500).map(|i| (i * 37 % 61) - 30).collect();
+    let timeline = dial.timeline(&rotations);
+    let mut passed = vec![0; 7];
+    let mut pos = dial.start;
+    for &rot in &rotations {
+        let end = dial.rotate(pos, rot);
+        for p in 0..7 {
+            passed[p as usize] += dial.crossings_of(pos, rot, p) - (p == end && rot != 0) as u64;
+        }
+        pos = end;
+    }
+    assert_eq!(timeline.passed, passed);
+}
+
+fn main() {
+    // the rotations are streamed from the file, and only read in memory when needed
+    let counts = if THREADS > 1 {
+        let rotations = read_file("inputs/puzzle1.txt", "\n", parse_rotation);
+        Dial::puzzle().counts_parallel(&rotations, THREADS)
+    } else {
+        let file = File::open("inputs/puzzle1.txt").unwrap();
+        Dial::puzzle()
+            .counts_streaming(io::BufReader::new(file))
+            .unwrap()
+    };
+    println!("Part 1: {}", part1(&counts));
+    println!("Part 2: {}", part2(&counts));
+    if TIMELINE_CSV.is_some() || HISTOGRAM_CSV.is_some() {
+        let rotations = read_file("inputs/puzzle1.txt", "\n", parse_rotation);
+        export_timeline(&rotations, TIMELINE_CSV, HISTOGRAM_CSV).unwrap();
+    }
+    if FIND_STARTS {
+        let rotations = read_file("inputs/puzzle1.txt", "\n", parse_rotation);
+        let passwords = [
+            (Method::Landings, part1(&counts)),
+            (Method::Crossings, part2(&counts)),
+        ];
+        for (method, password) in passwords {
+            let search = Dial::puzzle().find_starts(&rotations, method, password);
+            println!("{method:?}: password {password} from {:?}", search.matches);
+        }
+    }
 }
@@ -1,6 +1,10 @@
 use std::collections::HashMap;
 
-use aoc2025::read_file;
+use aoc2025::{
+    memo::Memo,
+    num::{Num, Overflow},
+    read_file,
+};
 
 /// A Server is a line in the input file.
 type Server = (String, Vec<String>);
@@ -10,45 +14,44 @@ type Server = (String, Vec<String>);
 type Rack = HashMap<String, Vec<String>>;
 
 /// Generates a rack from the vector of servers.
-fn rack_from_servers(servers: &Vec<Server>) -> Rack {
+fn rack_from_servers(servers: &[Server]) -> Rack {
     servers.iter().cloned().collect()
 }
 
-/// Memoized recursive implementation of `compute_paths`.
-fn compute_paths_aux<'a, 'b>(
+/// Memoized recursive implementation of `compute_paths`. The servers in `intermediates` which
+/// have not been traversed yet are encoded in the bitmask `missing`, so that memo keys are
+/// cheap to build.
+fn compute_paths_aux<'a, N: Num>(
     rack: &'a Rack,
     origin: &'a str,
-    intermediates: &Vec<&'b str>,
-    memo: &mut HashMap<(&'a str, Vec<&'b str>), u64>,
-) -> u64 {
+    intermediates: &[&str],
+    missing: u64,
+    memo: &mut Memo<(&'a str, u64), N>,
+) -> Result<N, Overflow> {
     if origin == "out" {
-        if intermediates.is_empty() { 1 } else { 0 }
-    } else {
-        // I don't like cloning intermediates here, but there is not a simple workaround
-        // since building a tuple requires taking owenership of the object.
-        match memo.get(&(origin, intermediates.clone())) {
-            Some(&v) => v,
-            None => {
-                let origin_index = intermediates.iter().position(|&x| x == origin);
-                let mut intermediates_new = intermediates.clone();
-                if let Some(i) = origin_index {
-                    intermediates_new.remove(i);
-                }
-                let result = rack[origin]
-                    .iter()
-                    .map(|dst| compute_paths_aux(rack, dst, &intermediates_new, memo))
-                    .sum();
-                memo.insert((origin, intermediates_new), result);
-                result
-            }
-        }
+        return Ok(if missing == 0 { N::one() } else { N::zero() });
     }
+    let missing = match intermediates.iter().position(|&x| x == origin) {
+        Some(i) => missing & !(1 << i),
+        None => missing,
+    };
+    if let Some(v) = memo.get(&(origin, missing)) {
+        return Ok(v.clone());
+    }
+    let mut result = N::zero();
+    for dst in &rack[origin] {
+        result = result.add(&compute_paths_aux(rack, dst, intermediates, missing, memo)?)?;
+    }
+    memo.insert((origin, missing), result.clone());
+    Ok(result)
 }
 
 /// Computes the number of paths in the rack from `origin` to the "out" servers,
 /// only considered those paths which traverse the servers in `intermediates`.
-fn compute_paths(rack: &Rack, intermediates: &Vec<&str>, origin: &str) -> u64 {
-    compute_paths_aux(rack, origin, intermediates, &mut HashMap::new())
+fn compute_paths<N: Num>(rack: &Rack, intermediates: &[&str], origin: &str) -> Result<N, Overflow> {
+    assert!(intermediates.len() < 64, "too many intermediate servers");
+    let missing = (1 << intermediates.len()) - 1;
+    compute_paths_aux(rack, origin, intermediates, missing, &mut Memo::new())
 }
 
 /// Parse on row of the input file.
@@ -58,29 +61,29 @@ fn parse_server(row: &str) -> Server {
     (key.to_string(), outs)
 }
 
-fn part1(servers: &Vec<Server>) -> u64 {
+fn part1<N: Num>(servers: &[Server]) -> Result<N, Overflow> {
     let rack = rack_from_servers(servers);
-    compute_paths(&rack, &vec![], "you")
+    compute_paths(&rack, &[], "you")
 }
 
-fn part2(servers: &Vec<Server>) -> u64 {
+fn part2<N: Num>(servers: &[Server]) -> Result<N, Overflow> {
     let rack = rack_from_servers(servers);
-    compute_paths(&rack, &vec!["fft", "dac"], "svr")
+    compute_paths(&rack, &["fft", "dac"], "svr")
 }
 
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle11_example.txt", "\n", parse_server);
-    assert_eq!(part1(&content), 5);
+    assert_eq!(part1::<u64>(&content), Ok(5));
     let content = read_file("inputs/puzzle11_example2.txt", "\n", parse_server);
-    assert_eq!(part2(&content), 2);
+    assert_eq!(part2::<u64>(&content), Ok(2));
     let content = read_file("inputs/puzzle11.txt", "\n", parse_server);
-    assert_eq!(part1(&content), 701);
-    assert_eq!(part2(&content), 390108778818526);
+    assert_eq!(part1::<u64>(&content), Ok(701));
+    assert_eq!(part2::<u64>(&content), Ok(390108778818526));
 }
 
 fn main() {
     let content = read_file("inputs/puzzle11.txt", "\n", parse_server);
-    println!("Part 1: {}", part1(&content));
-    println!("Part 2: {}", part2(&content));
+    println!("Part 1: {}", part1::<u64>(&content).unwrap());
+    println!("Part 2: {}", part2::<u64>(&content).unwrap());
 }
@@ -1,12 +1,31 @@
-use aoc2025::read_file;
+use aoc2025::{
+    image::{Image, Rgb, palette},
+    memo::{FnvBuildHasher, Memo, MemoStats},
+    read_file,
+};
 use std::{
-    collections::HashMap,
     fmt,
     ops::{Deref, DerefMut},
 };
 
 const DEBUG: bool = true;
 
+/// Set to true to print the usage statistics of the memo tables of `Region::packing`.
+const MEMO_STATS: bool = false;
+
+/// Set to a directory name to save an image of the packing of each feasible region.
+const EXPORT_DIR: Option<&str> = None;
+
+/// Maximum number of entries in the memo tables of `Region::packing`, shared evenly among
+/// them.
+const MEMO_LIMIT: usize = 1 << 16;
+
+/// Memo tables used by `Region::packing`, one for each index of the next shape to place,
+/// recording the states of the region which do not lead to a solution. Keying each table on
+/// the bitmap alone allows looking it up by reference, so that the bitmap is only cloned when
+/// a failure is recorded.
+type FailureMemo = Vec<Memo<Bitmap, (), FnvBuildHasher>>;
+
 /// A Bitmap used for both the shapes and the regions.
 ///
 /// The implementation is quite inefficient (a vector of vectors of booleans).
@@ -210,43 +229,89 @@ impl Region {
     ///   1. check that the solution declared feasible by `is_feasible_fast` is actually feasible;
     ///   2. correctly declare the third region in the example input as not feasible.
     fn is_feasible_slow(&self, shapes: &Vec<Shape>) -> bool {
+        self.packing(shapes).is_some()
+    }
+
+    /// Look for a way to accomodate the shapes inside the region. If it exists, return a
+    /// grid with, for each point of the region, the index of the shape which covers it in
+    /// the order of placement.
+    fn packing(&self, shapes: &Vec<Shape>) -> Option<Vec<Vec<Option<usize>>>> {
         if DEBUG {
             println!("{self:?}");
         }
         let bitmap = Bitmap::new(self.width, self.height);
-        let shape_list = self
+        let shape_list: Vec<usize> = self
             .requirements
             .iter()
             .enumerate()
             .flat_map(|(i, s)| vec![i; *s as usize])
             .collect();
-        Region::is_feasible_slow_aux(shapes, &shape_list, 0, &bitmap, &mut HashMap::new())
+        let limit = MEMO_LIMIT / shape_list.len().max(1);
+        let mut memo: FailureMemo = (0..shape_list.len())
+            .map(|_| Memo::with_hasher(FnvBuildHasher::default()).bounded(limit))
+            .collect();
+        let mut placements = Vec::new();
+        let found =
+            Region::packing_aux(shapes, &shape_list, 0, &bitmap, &mut placements, &mut memo);
+        if MEMO_STATS {
+            let stats = memo
+                .iter()
+                .map(|m| m.stats())
+                .fold(MemoStats::default(), |a, b| MemoStats {
+                    hits: a.hits + b.hits,
+                    misses: a.misses + b.misses,
+                    dropped: a.dropped + b.dropped,
+                });
+            println!("{stats:?}");
+        }
+        if !found {
+            return None;
+        }
+        // placements are collected from the last one, and each of them is the whole region
+        // after the placement, so a shape is what is new with respect to the previous bitmap
+        placements.reverse();
+        let mut result = vec![vec![None; self.width]; self.height];
+        let mut previous = &bitmap;
+        for (k, current) in placements.iter().enumerate() {
+            for i in 0..self.height {
+                for j in 0..self.width {
+                    if current[i][j] && !previous[i][j] {
+                        result[i][j] = Some(k);
+                    }
+                }
+            }
+            previous = current;
+        }
+        Some(result)
     }
 
-    /// Auxiliary function used in `is_feasible_slow`. Note that caching is only useful for the third
-    /// region of the example input, and might be removed.
-    fn is_feasible_slow_aux(
+    /// Auxiliary function used in `packing`. When a solution is found, the bitmaps of the
+    /// region after each placement are pushed on `placements`, starting from the last one.
+    /// Only failures are memoized, since a success ends the search. Note that caching is only
+    /// useful for the third region of the example input, and might be removed.
+    fn packing_aux(
         shapes: &Vec<Shape>,
         shape_list: &Vec<usize>,
         i: usize,
         region_bitmap: &Bitmap,
-        memo: &mut HashMap<(usize, Bitmap), bool>,
+        placements: &mut Vec<Bitmap>,
+        memo: &mut FailureMemo,
     ) -> bool {
         if i >= shape_list.len() {
             return true;
         }
-        if let Some(&v) = memo.get(&(i, region_bitmap.clone())) {
-            return v;
+        if memo[i].get(region_bitmap).is_some() {
+            return false;
         }
         for shape_bitmap in &shapes[shape_list[i]].bitmaps {
             for new_bitmap in region_bitmap.merge(shape_bitmap) {
-                if Region::is_feasible_slow_aux(shapes, shape_list, i + 1, &new_bitmap, memo) {
-                    memo.insert((i, region_bitmap.clone()), true);
+                if Region::packing_aux(shapes, shape_list, i + 1, &new_bitmap, placements, memo) {
+                    placements.push(new_bitmap);
                     return true;
                 }
             }
         }
-        memo.insert((i, region_bitmap.clone()), false);
+        memo[i].insert(region_bitmap.clone(), ());
         false
     }
 }
@@ -282,10 +347,31 @@ fn part1(content: &[String]) -> usize {
         .count()
 }
 
+/// Save an image of the packing of each feasible region in the directory `dir`.
+fn export_packings(content: &[String], dir: &str) -> std::io::Result<()> {
+    let problem = Problem::parse(content);
+    for (n, region) in problem.regions.iter().enumerate() {
+        if !region.is_feasible_fast(&problem.shapes) {
+            continue;
+        }
+        if let Some(packing) = region.packing(&problem.shapes) {
+            Image::from_grid(&packing, 8, |_i, _j, &shape| {
+                shape.map(palette).unwrap_or(Rgb::WHITE)
+            })
+            .save(format!("{dir}/puzzle12_region{n:04}.png"))?;
+        }
+    }
+    Ok(())
+}
+
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle12_example.txt", "\n", str::to_string);
     assert_eq!(part1(&content), 2);
+    let problem = Problem::parse(&content);
+    let packing = problem.regions[0].packing(&problem.shapes).unwrap();
+    let covered = packing.iter().flatten().filter(|x| x.is_some()).count();
+    assert_eq!(covered, 2 * problem.shapes[4].area);
     let content = read_file("inputs/puzzle12.txt", "\n", str::to_string);
     assert_eq!(part1(&content), 595);
 }
@@ -293,4 +379,7 @@ fn test() {
 fn main() {
     let content = read_file("inputs/puzzle12.txt", "\n", str::to_string);
     println!("Part 1: {}", part1(&content));
+    if let Some(dir) = EXPORT_DIR {
+        export_packings(&content, dir).unwrap();
+    }
 }
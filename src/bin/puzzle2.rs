@@ -1,17 +1,67 @@
-use aoc2025::read_file;
+use aoc2025::{
+    interval::{Discrete, IntervalSet},
+    num::Overflow,
+    read_file,
+};
+use std::{
+    fmt::{self, Debug, Display},
+    str::FromStr,
+};
 
-fn parse_interval(interval: &str) -> (u64, u64) {
+/// Set to `true` to print the count and the sum of the fake IDs in each interval.
+const REPORT: bool = false;
+
+/// Set to `true` to merge overlapping and adjacent intervals, so that no ID is counted twice.
+const MERGE_INTERVALS: bool = false;
+
+/// Set to `true` to also print the sum of the fake IDs according to some other rules.
+const OTHER_RULES: bool = false;
+
+/// An unsigned integer type for the IDs. All the computations are performed on `u128`.
+trait Id: Discrete + Display + FromStr + 'static {
+    fn to_u128(self) -> u128;
+
+    /// Convert back from `u128`, or return `None` if `n` is out of the range of the type.
+    fn from_u128(n: u128) -> Option<Self>;
+}
+
+impl Id for u64 {
+    fn to_u128(self) -> u128 {
+        self as u128
+    }
+
+    fn from_u128(n: u128) -> Option<Self> {
+        n.try_into().ok()
+    }
+}
+
+impl Id for u128 {
+    fn to_u128(self) -> u128 {
+        self
+    }
+
+    fn from_u128(n: u128) -> Option<Self> {
+        Some(n)
+    }
+}
+
+fn parse_interval<T: Id>(interval: &str) -> (T, T)
+where
+    T::Err: Debug,
+{
     let (x, y) = interval.split_once('-').unwrap();
     let first = x.parse().unwrap();
     let second = y.parse().unwrap();
     (first, second)
 }
 
+#[cfg(test)]
 fn is_fake_id1(i: &u64) -> bool {
     let bi = i.to_string().into_bytes();
     bi.len() % 2 == 0 && bi[..bi.len() / 2] == bi[bi.len() / 2..]
 }
 
+#[cfg(test)]
 fn is_fake_id2(i: &u64) -> bool {
     let bi = i.to_string().into_bytes();
     for baselen in 1..=bi.len() / 2 {
@@ -31,36 +81,812 @@ fn is_fake_id2(i: &u64) -> bool {
     false
 }
 
-fn count_fake_ids(interval: &(u64, u64), checker: fn(&u64) -> bool) -> u64 {
-    (interval.0..=interval.1).filter(checker).sum()
+/// Return the fake IDs in the interval by checking every integer. This is only feasible for
+/// small intervals, but it is useful for testing the faster `fake_id_stats` and `fake_ids`.
+#[cfg(test)]
+fn fake_ids_slow<T: Id>(interval: &(T, T), checker: impl Fn(&T) -> bool) -> Vec<T> {
+    (interval.0.to_u128()..=interval.1.to_u128())
+        .map(|id| T::from_u128(id).unwrap())
+        .filter(checker)
+        .collect()
+}
+
+/// Return the digits of `n` in base `radix`, starting from the most significant one.
+fn to_digits(mut n: u128, radix: u32) -> Vec<u32> {
+    let mut result = vec![(n % radix as u128) as u32];
+    while n >= radix as u128 {
+        n /= radix as u128;
+        result.push((n % radix as u128) as u32);
+    }
+    result.reverse();
+    result
+}
+
+/// Return the number of digits of `n` in base `radix`.
+fn digits(n: u128, radix: u32) -> u32 {
+    n.checked_ilog(radix as u128).unwrap_or(0) + 1
+}
+
+/// Return the divisors of `n` smaller than `n`, in increasing order.
+fn proper_divisors(n: u32) -> Vec<u32> {
+    (1..n).filter(|d| n.is_multiple_of(*d)).collect()
+}
+
+/// The number of times the block of a fake ID may be repeated. A block is always repeated at
+/// least twice, whatever the bounds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Repetitions {
+    Exactly(u32),
+    AtLeast(u32),
+    AtMost(u32),
+}
+
+impl Repetitions {
+    /// Determine whether a block may be repeated `k` times.
+    fn allows(self, k: u32) -> bool {
+        k >= 2
+            && match self {
+                Repetitions::Exactly(n) => k == n,
+                Repetitions::AtLeast(n) => k >= n,
+                Repetitions::AtMost(n) => k <= n,
+            }
+    }
+}
+
+/// A property of the digits of an ID which makes it fake.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Rule {
+    /// The ID, written in base `radix`, is a block of digits repeated a number of times
+    /// allowed by `count`.
+    Repeated { count: Repetitions, radix: u32 },
+    /// The ID, written in base `radix`, is a palindrome.
+    Palindrome { radix: u32 },
+}
+
+impl Rule {
+    fn radix(self) -> u32 {
+        match self {
+            Rule::Repeated { radix, .. } | Rule::Palindrome { radix } => radix,
+        }
+    }
+
+    /// Determine whether the rule holds for `id`.
+    fn matches(self, id: u128) -> bool {
+        let digits = to_digits(id, self.radix());
+        match self {
+            Rule::Repeated { count, .. } => {
+                let len = digits.len();
+                proper_divisors(len as u32).into_iter().any(|base| {
+                    count.allows(len as u32 / base)
+                        && (base as usize..len).all(|j| digits[j] == digits[j - base as usize])
+                })
+            }
+            Rule::Palindrome { .. } => digits.iter().eq(digits.iter().rev()),
+        }
+    }
+
+    /// Return the fake IDs in the interval according to this rule, in increasing order.
+    fn ids(self, interval: (u128, u128)) -> Box<dyn Iterator<Item = u128>> {
+        match self {
+            Rule::Repeated { count, radix } => Box::new(RepeatedIds::new(interval, count, radix)),
+            Rule::Palindrome { radix } => Box::new(Palindromes::new(interval, radix)),
+        }
+    }
+}
+
+/// How the rules of a `Rules` are combined.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Combine {
+    /// An ID is fake if any rule holds.
+    Any,
+    /// An ID is fake if all the rules hold.
+    All,
+}
+
+/// Error returned when a set of rules cannot be used.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum InvalidRules {
+    /// There is no rule, so every ID would be fake.
+    Empty,
+    /// A rule uses a base smaller than 2.
+    Radix(u32),
+}
+
+impl fmt::Display for InvalidRules {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InvalidRules::Empty => write!(f, "empty set of rules"),
+            InvalidRules::Radix(radix) => write!(f, "invalid radix {radix}"),
+        }
+    }
+}
+
+impl std::error::Error for InvalidRules {}
+
+/// A set of rules which determine whether an ID is fake.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Rules {
+    rules: Vec<Rule>,
+    combine: Combine,
+}
+
+impl Rules {
+    /// Combine `rules` as described by `combine`, checking that there is at least one rule
+    /// and that their bases are valid.
+    fn new(rules: Vec<Rule>, combine: Combine) -> Result<Self, InvalidRules> {
+        if rules.is_empty() {
+            return Err(InvalidRules::Empty);
+        }
+        if let Some(rule) = rules.iter().find(|rule| rule.radix() < 2) {
+            return Err(InvalidRules::Radix(rule.radix()));
+        }
+        Ok(Self { rules, combine })
+    }
+
+    /// The IDs made of a block of decimal digits repeated twice, as in part 1.
+    fn part1() -> Self {
+        Self {
+            rules: vec![Rule::Repeated {
+                count: Repetitions::Exactly(2),
+                radix: 10,
+            }],
+            combine: Combine::Any,
+        }
+    }
+
+    /// The IDs made of a block of decimal digits repeated at least twice, as in part 2.
+    fn part2() -> Self {
+        Self {
+            rules: vec![Rule::Repeated {
+                count: Repetitions::AtLeast(2),
+                radix: 10,
+            }],
+            combine: Combine::Any,
+        }
+    }
+
+    /// Combine the results of the rules on `id`, computed by `holds`.
+    fn combine(&self, holds: impl Fn(&Rule) -> bool) -> bool {
+        match self.combine {
+            Combine::Any => self.rules.iter().any(holds),
+            Combine::All => self.rules.iter().all(holds),
+        }
+    }
+
+    /// Determine whether `id` is fake.
+    #[cfg(test)]
+    fn matches<T: Id>(&self, id: T) -> bool {
+        self.combine(|rule| rule.matches(id.to_u128()))
+    }
+
+    /// If all the rules are about repetitions in the same base, return that base.
+    fn repeated_radix(&self) -> Option<u32> {
+        let radix = self.rules.first()?.radix();
+        self.rules
+            .iter()
+            .all(|rule| matches!(rule, Rule::Repeated { .. }) && rule.radix() == radix)
+            .then_some(radix)
+    }
+}
+
+/// The number of fake IDs in a range of integers, and their sum.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+struct FakeIdStats {
+    count: u128,
+    sum: u128,
+}
+
+impl FakeIdStats {
+    /// Return the statistics of the union of two disjoint sets of IDs.
+    fn checked_add(self, other: Self) -> Result<Self, Overflow> {
+        Ok(Self {
+            count: self.count.checked_add(other.count).ok_or(Overflow)?,
+            sum: self.sum.checked_add(other.sum).ok_or(Overflow)?,
+        })
+    }
+
+    /// Return the statistics of the IDs in `self` which are not in `other`, which must be a
+    /// subset of them.
+    fn without(self, other: Self) -> Self {
+        Self {
+            count: self.count - other.count,
+            sum: self.sum - other.sum,
+        }
+    }
+
+    /// Return the statistics of the union of disjoint sets of IDs.
+    fn total(stats: impl IntoIterator<Item = Self>) -> Result<Self, Overflow> {
+        stats
+            .into_iter()
+            .try_fold(Self::default(), |acc, s| acc.checked_add(s))
+    }
+
+    /// Count and sum the distinct `ids`.
+    fn from_ids<T: Id>(ids: impl IntoIterator<Item = T>) -> Result<Self, Overflow> {
+        Self::total(ids.into_iter().map(|id| Self {
+            count: 1,
+            sum: id.to_u128(),
+        }))
+    }
+}
+
+/// Return the smallest number with `len` digits in base `radix`, assuming that it fits.
+fn min_with_digits(len: u32, radix: u32) -> u128 {
+    if len == 1 {
+        0
+    } else {
+        (radix as u128).pow(len - 1)
+    }
+}
+
+/// Return the largest number with `len` digits in base `radix`, or `u128::MAX` if it is larger.
+fn max_with_digits(len: u32, radix: u32) -> u128 {
+    (radix as u128)
+        .checked_pow(len)
+        .map_or(u128::MAX, |p| p - 1)
+}
+
+/// The numbers with `len` digits in base `radix` which are made of a block of `base` digits
+/// repeated `len / base` times are `pattern * (radix^len - 1) / (radix^base - 1)`, where
+/// `pattern` has exactly `base` digits. Return the multiplier and the smallest and largest
+/// patterns such that the numbers are in `[lo, hi]`, or `None` if the multiplier does not
+/// fit `u128`, in which case all these numbers are too large.
+fn repeated_patterns(
+    lo: u128,
+    hi: u128,
+    len: u32,
+    base: u32,
+    radix: u32,
+) -> Option<(u128, u128, u128)> {
+    let block = (radix as u128).pow(base);
+    let repunit =
+        (0..len / base).try_fold(0u128, |acc, _| acc.checked_mul(block)?.checked_add(1))?;
+    let min = (block / radix as u128).max(lo.div_ceil(repunit));
+    let max = (block - 1).min(hi / repunit);
+    Some((repunit, min, max))
 }
 
-fn part1(intervals: &Vec<(u64, u64)>) -> u64 {
+/// Compute the count and the sum of the numbers in `[lo, hi]` with `len` digits which are made
+/// of a block of `base` digits repeated.
+fn stats_repeated(
+    lo: u128,
+    hi: u128,
+    len: u32,
+    base: u32,
+    radix: u32,
+) -> Result<FakeIdStats, Overflow> {
+    let Some((repunit, min, max)) = repeated_patterns(lo, hi, len, base, radix) else {
+        return Ok(FakeIdStats::default());
+    };
+    if min > max {
+        return Ok(FakeIdStats::default());
+    }
+    let count = max - min + 1;
+    // either `count` or `min + max` is even
+    let pattern_sum = if count.is_multiple_of(2) {
+        (count / 2).checked_mul(min + max)
+    } else {
+        count.checked_mul((min + max) / 2)
+    };
+    let sum = pattern_sum.and_then(|s| s.checked_mul(repunit));
+    Ok(FakeIdStats {
+        count,
+        sum: sum.ok_or(Overflow)?,
+    })
+}
+
+/// Return the part of `interval` made of numbers with `len` digits in base `radix`.
+fn clamp_to_length(interval: (u128, u128), len: u32, radix: u32) -> (u128, u128) {
+    let lo = interval.0.max(min_with_digits(len, radix));
+    let hi = interval.1.min(max_with_digits(len, radix));
+    (lo, hi)
+}
+
+/// Count and sum the fake IDs in the interval.
+///
+/// When all the rules are about repetitions in the same base, this does not enumerate the IDs,
+/// and the time required only depends on the number of digits of the interval. The IDs with
+/// `len` digits are partitioned according to their minimal period `c`, that is the length of
+/// their shortest repeated block: such an ID is made of `k` repetitions exactly when `k`
+/// divides `len / c`, so each rule either holds for all the IDs with minimal period `c`, or
+/// for none. The IDs with period `c` are those with minimal period dividing `c`, which gives
+/// the statistics for each minimal period by difference.
+///
+/// Otherwise, the fake IDs are enumerated by `fake_ids`.
+fn fake_id_stats<T: Id>(interval: &(T, T), rules: &Rules) -> Result<FakeIdStats, Overflow> {
+    let Some(radix) = rules.repeated_radix() else {
+        return FakeIdStats::from_ids(fake_ids(interval, rules));
+    };
+    let (start, end) = (interval.0.to_u128(), interval.1.to_u128());
+    if start > end {
+        return Ok(FakeIdStats::default());
+    }
+    let mut total = FakeIdStats::default();
+    for len in digits(start, radix)..=digits(end, radix) {
+        let (lo, hi) = clamp_to_length((start, end), len, radix);
+        // statistics of the IDs with each minimal period, in increasing order
+        let mut minimal: Vec<(u32, FakeIdStats)> = Vec::new();
+        for c in proper_divisors(len) {
+            let periodic = stats_repeated(lo, hi, len, c, radix)?;
+            let shorter = FakeIdStats::total(
+                minimal
+                    .iter()
+                    .filter(|(d, _)| c.is_multiple_of(*d))
+                    .map(|(_, stats)| *stats),
+            )?;
+            let stats = periodic.without(shorter);
+            minimal.push((c, stats));
+            let fake = rules.combine(|rule| match rule {
+                Rule::Repeated { count, .. } => proper_divisors(len / c)
+                    .into_iter()
+                    .chain([len / c])
+                    .any(|k| count.allows(k)),
+                Rule::Palindrome { .. } => unreachable!(),
+            });
+            if fake {
+                total = total.checked_add(stats)?;
+            }
+        }
+    }
+    Ok(total)
+}
+
+/// Iterator over the numbers in an interval which are made of a block of digits repeated, in
+/// increasing order. The numbers are generated lazily, one length at a time, merging the
+/// sequences of the numbers made of each allowed number of repetitions.
+struct RepeatedIds {
+    interval: (u128, u128),
+    count: Repetitions,
+    radix: u32,
+    /// Number of digits of the IDs currently generated.
+    len: u32,
+    /// For each block, the next ID, the difference between consecutive IDs and the number of
+    /// IDs left.
+    cursors: Vec<(u128, u128, u128)>,
+}
+
+impl RepeatedIds {
+    fn new(interval: (u128, u128), count: Repetitions, radix: u32) -> Self {
+        let mut result = Self {
+            interval,
+            count,
+            radix,
+            len: digits(interval.0, radix),
+            cursors: Vec::new(),
+        };
+        if interval.0 <= interval.1 {
+            result.start_length();
+        }
+        result
+    }
+
+    /// Prepare the cursors for the IDs with `self.len` digits.
+    fn start_length(&mut self) {
+        let (len, radix) = (self.len, self.radix);
+        let (lo, hi) = clamp_to_length(self.interval, len, radix);
+        self.cursors = proper_divisors(len)
+            .into_iter()
+            .filter(|base| self.count.allows(len / base))
+            .filter_map(|base| repeated_patterns(lo, hi, len, base, radix))
+            .filter(|&(_, min, max)| min <= max)
+            .map(|(repunit, min, max)| (min * repunit, repunit, max - min + 1))
+            .collect();
+    }
+}
+
+impl Iterator for RepeatedIds {
+    type Item = u128;
+
+    fn next(&mut self) -> Option<u128> {
+        while self.cursors.is_empty() {
+            if self.len >= digits(self.interval.1, self.radix) {
+                return None;
+            }
+            self.len += 1;
+            self.start_length();
+        }
+        let id = self.cursors.iter().map(|c| c.0).min().unwrap();
+        // advance all the sequences which generate the same ID, to avoid duplicates
+        for cursor in self.cursors.iter_mut().filter(|c| c.0 == id) {
+            cursor.2 -= 1;
+            if cursor.2 > 0 {
+                cursor.0 += cursor.1;
+            }
+        }
+        self.cursors.retain(|c| c.2 > 0);
+        Some(id)
+    }
+}
+
+/// Iterator over the palindromes in an interval, in increasing order. A palindrome with `len`
+/// digits is determined by its first `(len + 1) / 2` digits, its half, and palindromes of the
+/// same length are in the same order as their halves.
+struct Palindromes {
+    interval: (u128, u128),
+    radix: u32,
+    /// Number of digits of the palindromes currently generated.
+    len: u32,
+    /// The next half and the last one with the current length.
+    half: u128,
+    last: u128,
+}
+
+impl Palindromes {
+    fn new(interval: (u128, u128), radix: u32) -> Self {
+        let mut result = Self {
+            interval,
+            radix,
+            len: digits(interval.0, radix),
+            half: 1,
+            last: 0,
+        };
+        if interval.0 <= interval.1 {
+            result.start_length();
+        }
+        result
+    }
+
+    /// Return the palindrome with `self.len` digits whose first half is `half`, or `None` if
+    /// it does not fit `u128`.
+    fn palindrome(&self, half: u128) -> Option<u128> {
+        let r = self.radix as u128;
+        let mut result = half;
+        let mut rest = if self.len % 2 == 1 { half / r } else { half };
+        for _ in 0..self.len / 2 {
+            result = result.checked_mul(r)?.checked_add(rest % r)?;
+            rest /= r;
+        }
+        Some(result)
+    }
+
+    /// Determine the range of halves for the palindromes with `self.len` digits.
+    fn start_length(&mut self) {
+        let (lo, hi) = clamp_to_length(self.interval, self.len, self.radix);
+        let shift = (self.radix as u128).pow(self.len / 2);
+        self.half = lo / shift;
+        if self.palindrome(self.half).is_some_and(|p| p < lo) {
+            self.half += 1;
+        }
+        self.last = hi / shift;
+        if self.palindrome(self.last).is_none_or(|p| p > hi) {
+            // `last` is positive when there are at least two digits, and a single digit is a
+            // palindrome
+            self.last -= 1;
+        }
+    }
+}
+
+impl Iterator for Palindromes {
+    type Item = u128;
+
+    fn next(&mut self) -> Option<u128> {
+        while self.half > self.last {
+            if self.len >= digits(self.interval.1, self.radix) {
+                return None;
+            }
+            self.len += 1;
+            self.start_length();
+        }
+        self.half += 1;
+        self.palindrome(self.half - 1)
+    }
+}
+
+/// Iterator over the union of increasing sequences, without duplicates.
+struct Union {
+    sequences: Vec<std::iter::Peekable<Box<dyn Iterator<Item = u128>>>>,
+}
+
+impl Iterator for Union {
+    type Item = u128;
+
+    fn next(&mut self) -> Option<u128> {
+        let id = *self.sequences.iter_mut().filter_map(|s| s.peek()).min()?;
+        for sequence in &mut self.sequences {
+            sequence.next_if_eq(&id);
+        }
+        Some(id)
+    }
+}
+
+/// Return the fake IDs in the interval in increasing order, generated lazily. For `Any`, this
+/// merges the sequences of the IDs of each rule, otherwise it filters the IDs of the first
+/// rule according to the other ones.
+fn fake_ids<'a, T: Id>(interval: &(T, T), rules: &'a Rules) -> Box<dyn Iterator<Item = T> + 'a> {
+    let interval = (interval.0.to_u128(), interval.1.to_u128());
+    let ids: Box<dyn Iterator<Item = u128>> = match rules.combine {
+        Combine::Any => Box::new(Union {
+            sequences: rules
+                .rules
+                .iter()
+                .map(|rule| rule.ids(interval).peekable())
+                .collect(),
+        }),
+        Combine::All => {
+            let (first, others) = rules.rules.split_first().expect("the rules are not empty");
+            Box::new(
+                first
+                    .ids(interval)
+                    .filter(move |&id| others.iter().all(|rule| rule.matches(id))),
+            )
+        }
+    };
+    Box::new(ids.map(|id| T::from_u128(id).expect("the fake IDs lie in the interval")))
+}
+
+/// The fake IDs found in one of the intervals of the input.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct IntervalReport<T> {
+    interval: (T, T),
+    stats: FakeIdStats,
+}
+
+/// Compute the count and the sum of the fake IDs in each interval.
+fn report<T: Id>(intervals: &[(T, T)], rules: &Rules) -> Result<Vec<IntervalReport<T>>, Overflow> {
     intervals
         .iter()
-        .map(|i| count_fake_ids(i, is_fake_id1))
-        .sum()
+        .map(|interval| {
+            Ok(IntervalReport {
+                interval: *interval,
+                stats: fake_id_stats(interval, rules)?,
+            })
+        })
+        .collect()
 }
 
-fn part2(intervals: &Vec<(u64, u64)>) -> u64 {
-    intervals
+/// Print the reports of the intervals, starting from the one with the largest sum, together
+/// with the first fake IDs of each interval.
+fn print_report<T: Id>(
+    name: &str,
+    reports: &[IntervalReport<T>],
+    rules: &Rules,
+) -> Result<(), Overflow> {
+    const SHOWN: usize = 5;
+    let total = FakeIdStats::total(reports.iter().map(|r| r.stats))?;
+    let mut reports = reports.to_vec();
+    reports.sort_by_key(|r| std::cmp::Reverse(r.stats.sum));
+    println!("{name}: {} fake IDs, sum {}", total.count, total.sum);
+    for r in reports {
+        let ids: Vec<String> = fake_ids(&r.interval, rules)
+            .take(SHOWN)
+            .map(|id| id.to_string())
+            .collect();
+        let more = if r.stats.count > SHOWN as u128 {
+            ", ..."
+        } else {
+            ""
+        };
+        println!(
+            "  {}-{}: {} fake IDs, sum {} ({:.1}%) [{}{more}]",
+            r.interval.0,
+            r.interval.1,
+            r.stats.count,
+            r.stats.sum,
+            100.0 * r.stats.sum as f64 / total.sum.max(1) as f64,
+            ids.join(", ")
+        );
+    }
+    Ok(())
+}
+
+/// Sum the fake IDs in all the intervals, according to `rules`. If `merge` is `true`, the
+/// intervals are normalized first, so that the IDs in more than one interval are counted once.
+fn sum_fake_ids<T: Id>(intervals: &[(T, T)], rules: &Rules, merge: bool) -> Result<u128, Overflow> {
+    let merged;
+    let intervals = if merge {
+        merged = IntervalSet::new(intervals);
+        merged.intervals()
+    } else {
+        intervals
+    };
+    let stats = intervals
         .iter()
-        .map(|i| count_fake_ids(i, is_fake_id2))
-        .sum()
+        .map(|i| fake_id_stats(i, rules))
+        .collect::<Result<Vec<_>, _>>()?;
+    Ok(FakeIdStats::total(stats)?.sum)
+}
+
+fn part1<T: Id>(intervals: &[(T, T)]) -> Result<u128, Overflow> {
+    sum_fake_ids(intervals, &Rules::part1(), MERGE_INTERVALS)
+}
+
+fn part2<T: Id>(intervals: &[(T, T)]) -> Result<u128, Overflow> {
+    sum_fake_ids(intervals, &Rules::part2(), MERGE_INTERVALS)
 }
 
 #[test]
 fn test() {
-    let intervals = read_file("inputs/puzzle2_example.txt", ",", parse_interval);
-    assert_eq!(part1(&intervals), 1227775554);
-    assert_eq!(part2(&intervals), 4174379265);
-    let intervals = read_file("inputs/puzzle2.txt", ",", parse_interval);
-    assert_eq!(part1(&intervals), 54641809925);
-    assert_eq!(part2(&intervals), 73694270688);
+    let intervals = read_file("inputs/puzzle2_example.txt", ",", parse_interval::<u64>);
+    assert_eq!(part1(&intervals), Ok(1227775554));
+    assert_eq!(part2(&intervals), Ok(4174379265));
+    let intervals = read_file("inputs/puzzle2.txt", ",", parse_interval::<u64>);
+    assert_eq!(part1(&intervals), Ok(54641809925));
+    assert_eq!(part2(&intervals), Ok(73694270688));
+}
+
+#[test]
+fn test_fake_ids() {
+    for interval in [
+        (0, 0),
+        (1, 9),
+        (5, 3),
+        (1, 200_000),
+        (95, 1_212_121),
+        (222_222, 222_222),
+    ] {
+        for (checker, rules) in [
+            (is_fake_id1 as fn(&u64) -> bool, Rules::part1()),
+            (is_fake_id2, Rules::part2()),
+        ] {
+            let expected = fake_ids_slow(&interval, checker);
+            let stats = FakeIdStats::from_ids(expected.iter().copied());
+            assert_eq!(fake_id_stats(&interval, &rules), stats);
+            assert_eq!(fake_ids(&interval, &rules).collect::<Vec<_>>(), expected);
+        }
+    }
+    // intervals which are too wide for a brute force check
+    let stats = fake_id_stats(&(1u64 << 62, 1 << 62), &Rules::part2());
+    assert_eq!(stats.unwrap().count, 0);
+    let all = fake_id_stats(&(1, u64::MAX), &Rules::part2()).unwrap();
+    assert!(all.sum > u64::MAX as u128);
+    let rules = Rules::part2();
+    let mut ids = fake_ids(&(1, u64::MAX), &rules);
+    assert_eq!(ids.nth(9), Some(111));
+    assert_eq!(ids.nth(8), Some(1010));
+    assert_eq!(fake_ids(&(1, u64::MAX), &Rules::part1()).nth(3), Some(44));
+
+    let intervals = read_file("inputs/puzzle2_example.txt", ",", parse_interval::<u64>);
+    let reports = report(&intervals, &Rules::part1()).unwrap();
+    assert_eq!(reports.len(), intervals.len());
+    assert_eq!(reports[0].interval, (11, 22));
+    assert_eq!(reports[0].stats, FakeIdStats { count: 2, sum: 33 });
+    let total = FakeIdStats::total(reports.iter().map(|r| r.stats)).unwrap();
+    assert_eq!(Ok(total.sum), part1(&intervals));
+}
+
+#[test]
+fn test_rules() {
+    use Repetitions::*;
+    let repeated = |count, radix| Rule::Repeated { count, radix };
+    let rule_sets = [
+        (vec![repeated(Exactly(3), 10)], Combine::Any),
+        (vec![repeated(AtMost(3), 10)], Combine::Any),
+        (vec![repeated(AtLeast(3), 2)], Combine::Any),
+        (
+            vec![repeated(Exactly(2), 16), repeated(Exactly(3), 16)],
+            Combine::Any,
+        ),
+        (
+            vec![repeated(AtLeast(2), 2), repeated(AtMost(4), 2)],
+            Combine::All,
+        ),
+        (
+            vec![repeated(Exactly(2), 10), repeated(Exactly(3), 10)],
+            Combine::All,
+        ),
+        (vec![Rule::Palindrome { radix: 10 }], Combine::Any),
+        (
+            vec![Rule::Palindrome { radix: 2 }, repeated(AtLeast(2), 2)],
+            Combine::All,
+        ),
+        (
+            vec![Rule::Palindrome { radix: 16 }, repeated(Exactly(2), 10)],
+            Combine::Any,
+        ),
+    ];
+    for (rules, combine) in rule_sets {
+        let rules = Rules::new(rules, combine).unwrap();
+        for interval in [(0u64, 100_000), (123_456, 150_000), (777, 777)] {
+            let expected = fake_ids_slow(&interval, |&id| rules.matches(id));
+            let stats = FakeIdStats::from_ids(expected.iter().copied());
+            assert_eq!(fake_id_stats(&interval, &rules), stats, "{rules:?}");
+            assert_eq!(fake_ids(&interval, &rules).collect::<Vec<_>>(), expected);
+        }
+    }
+    assert!(Rule::Palindrome { radix: 10 }.matches(0));
+    assert!(repeated(Exactly(6), 2).matches(0b111111));
+    assert!(!repeated(Exactly(4), 2).matches(0b111111));
+    assert!(repeated(AtMost(2), 16).matches(0xabab));
+    assert_eq!(
+        Rules::new(
+            vec![repeated(AtLeast(2), 10), repeated(Exactly(2), 1)],
+            Combine::Any
+        ),
+        Err(InvalidRules::Radix(1))
+    );
+    assert_eq!(
+        Rules::new(vec![Rule::Palindrome { radix: 0 }], Combine::All),
+        Err(InvalidRules::Radix(0))
+    );
+    for combine in [Combine::Any, Combine::All] {
+        assert_eq!(Rules::new(vec![], combine), Err(InvalidRules::Empty));
+    }
+    assert_eq!(u64::from_u128(u64::MAX as u128 + 1), None);
+    assert_eq!(u64::from_u128(u64::MAX as u128), Some(u64::MAX));
+}
+
+#[test]
+fn test_wide_ids() {
+    let intervals = read_file("inputs/puzzle2.txt", ",", parse_interval::<u128>);
+    assert_eq!(part2(&intervals), Ok(73694270688));
+
+    // IDs which do not fit `u64`
+    let rules = Rules::part2();
+    let interval = (10u128.pow(37), 10u128.pow(37) + 10u128.pow(20));
+    let ids: Vec<u128> = fake_ids(&interval, &rules).collect();
+    assert_eq!(ids[0], 10u128.pow(18) * (10u128.pow(19) + 1));
+    assert!(ids.iter().all(|&id| rules.matches(id)));
+    assert_eq!(
+        fake_id_stats(&interval, &rules),
+        FakeIdStats::from_ids(ids.iter().copied())
+    );
+    assert_eq!(fake_id_stats(&(0, u128::MAX), &rules), Err(Overflow));
+
+    let rules = Rules::new(
+        vec![
+            Rule::Repeated {
+                count: Repetitions::AtLeast(2),
+                radix: 2,
+            },
+            Rule::Palindrome { radix: 2 },
+        ],
+        Combine::Any,
+    )
+    .unwrap();
+    let interval = (u128::MAX - 100_000, u128::MAX);
+    let expected = fake_ids_slow(&interval, |&id| rules.matches(id));
+    assert_eq!(expected.last(), Some(&u128::MAX));
+    assert_eq!(fake_ids(&interval, &rules).collect::<Vec<_>>(), expected);
+}
+
+#[test]
+fn test_merge() {
+    let intervals = vec![(11u64, 22), (15, 33), (34, 44), (50, 40)];
+    let rules = Rules::part1();
+    assert_eq!(
+        sum_fake_ids(&intervals, &rules, false),
+        Ok(11 + 22 + 22 + 33 + 44)
+    );
+    assert_eq!(
+        sum_fake_ids(&intervals, &rules, true),
+        Ok(11 + 22 + 33 + 44)
+    );
 }
 
 fn main() {
-    let intervals = read_file("inputs/puzzle2.txt", ",", parse_interval);
-    println!("Part 1: {}", part1(&intervals));
-    println!("Part 2: {}", part2(&intervals));
+    let intervals = read_file("inputs/puzzle2.txt", ",", parse_interval::<u64>);
+    println!("Part 1: {}", part1(&intervals).unwrap());
+    println!("Part 2: {}", part2(&intervals).unwrap());
+    if REPORT {
+        for (name, rules) in [("Part 1", Rules::part1()), ("Part 2", Rules::part2())] {
+            let reports = report(&intervals, &rules).unwrap();
+            print_report(name, &reports, &rules).unwrap();
+        }
+    }
+    if OTHER_RULES {
+        use Repetitions::*;
+        let repeated = |count, radix| Rule::Repeated { count, radix };
+        let rule_sets = [
+            (
+                "Blocks repeated at most 3 times",
+                vec![repeated(AtMost(3), 10)],
+                Combine::Any,
+            ),
+            (
+                "Palindromes",
+                vec![Rule::Palindrome { radix: 10 }],
+                Combine::Any,
+            ),
+            (
+                "Repeated binary palindromes",
+                vec![Rule::Palindrome { radix: 2 }, repeated(AtLeast(2), 2)],
+                Combine::All,
+            ),
+        ];
+        for (name, rules, combine) in rule_sets {
+            let rules = Rules::new(rules, combine).unwrap();
+            let sum = sum_fake_ids(&intervals, &rules, MERGE_INTERVALS).unwrap();
+            println!("{name}: {sum}");
+        }
+    }
 }
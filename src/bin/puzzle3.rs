@@ -1,10 +1,88 @@
-use aoc2025::read_file;
+use aoc2025::{
+    num::{self, Num, Overflow},
+    read_file, selection,
+};
+use std::{fmt, ops::Deref};
 
-fn parse_bank(bank: &str) -> Vec<u8> {
-    bank.as_bytes().iter().map(|x| x - b'0').collect()
+/// Error returned when a bank cannot be parsed or is not suitable for a selection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum BankError {
+    /// The bank contains a character which is not a decimal digit.
+    InvalidDigit { position: usize, found: char },
+    /// The bank has fewer digits than those to be selected.
+    TooShort { len: usize, required: usize },
+    /// The selected number does not fit the numeric backend.
+    Overflow,
 }
 
-fn max_bank(bank: &Vec<u8>, digits: usize) -> u64 {
+impl fmt::Display for BankError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            BankError::InvalidDigit { position, found } => {
+                write!(f, "invalid digit {found:?} at position {position}")
+            }
+            BankError::TooShort { len, required } => {
+                write!(f, "bank of {len} digits, but {required} are required")
+            }
+            BankError::Overflow => write!(f, "{Overflow}"),
+        }
+    }
+}
+
+impl std::error::Error for BankError {}
+
+impl From<Overflow> for BankError {
+    fn from(_: Overflow) -> Self {
+        BankError::Overflow
+    }
+}
+
+/// A bank of batteries, whose joltages are guaranteed to be decimal digits.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Bank(Vec<u8>);
+
+impl Deref for Bank {
+    type Target = [u8];
+
+    fn deref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl Bank {
+    /// Check that the bank has at least `digits` digits.
+    fn check_len(&self, digits: usize) -> Result<(), BankError> {
+        if self.len() < digits {
+            return Err(BankError::TooShort {
+                len: self.len(),
+                required: digits,
+            });
+        }
+        Ok(())
+    }
+}
+
+fn parse_bank(bank: &str) -> Result<Bank, BankError> {
+    bank.chars()
+        .enumerate()
+        .map(|(position, c)| match c.to_digit(10) {
+            Some(d) => Ok(d as u8),
+            None => Err(BankError::InvalidDigit { position, found: c }),
+        })
+        .collect::<Result<_, _>>()
+        .map(Bank)
+}
+
+/// Read and validate all the banks in the file `filename`, one for each line.
+fn read_banks(filename: &str) -> Result<Vec<Bank>, BankError> {
+    read_file(filename, "\n", parse_bank).into_iter().collect()
+}
+
+/// Compute the largest number made of `digits` digits of `bank`, taken in order, by choosing
+/// each digit as the largest one which leaves enough digits for the rest. This takes time
+/// proportional to the length of the bank times `digits`.
+#[cfg(test)]
+fn max_bank_slow(bank: &[u8], digits: usize) -> u64 {
     let mut idx = 0;
     let mut val = 0;
     for i in 0..digits {
@@ -19,26 +97,140 @@ fn max_bank(bank: &Vec<u8>, digits: usize) -> u64 {
     val
 }
 
-fn part1(banks: &Vec<Vec<u8>>) -> u64 {
-    banks.iter().map(|bank| max_bank(bank, 2)).sum()
+/// Compute the largest number made of `digits` digits of `bank`, taken in order, together
+/// with the indices of the chosen digits. With a `BigUint`, any number of digits is allowed.
+fn max_bank<N: Num>(bank: &Bank, digits: usize) -> Result<(N, Vec<usize>), BankError> {
+    bank.check_len(digits)?;
+    let indices = selection::max_indices(bank, digits);
+    let ten = N::from_u64(10);
+    let val = indices.iter().try_fold(N::zero(), |val, &i| {
+        val.mul(&ten)?.add(&N::from_u64(bank[i] as u64))
+    })?;
+    Ok((val, indices))
 }
 
-fn part2(banks: &Vec<Vec<u8>>) -> u64 {
-    banks.iter().map(|bank| max_bank(bank, 12)).sum()
+/// Return the largest number made of `digits` digits of `bank` as a string of decimal digits.
+#[cfg(test)]
+fn max_bank_string(bank: &Bank, digits: usize) -> Result<String, BankError> {
+    bank.check_len(digits)?;
+    Ok(selection::to_string(
+        bank,
+        &selection::max_indices(bank, digits),
+    ))
+}
+
+/// Sum the largest numbers made of `digits` digits of each bank.
+fn total_joltage<N: Num>(banks: &[Bank], digits: usize) -> Result<N, BankError> {
+    let values = banks
+        .iter()
+        .map(|bank| max_bank(bank, digits).map(|(val, _)| val))
+        .collect::<Result<Vec<N>, _>>()?;
+    Ok(num::sum(values)?)
+}
+
+fn part1<N: Num>(banks: &[Bank]) -> Result<N, BankError> {
+    total_joltage(banks, 2)
+}
+
+fn part2<N: Num>(banks: &[Bank]) -> Result<N, BankError> {
+    total_joltage(banks, 12)
 }
 
 #[test]
 fn test() {
-    let banks = read_file("inputs/puzzle3_example.txt", "\n", parse_bank);
-    assert_eq!(part1(&banks), 357);
-    assert_eq!(part2(&banks), 3121910778619);
-    let banks = read_file("inputs/puzzle3.txt", "\n", parse_bank);
-    assert_eq!(part1(&banks), 17158);
-    assert_eq!(part2(&banks), 170449335646486);
+    let banks = read_banks("inputs/puzzle3_example.txt").unwrap();
+    assert_eq!(part1::<u64>(&banks), Ok(357));
+    assert_eq!(part2::<u64>(&banks), Ok(3121910778619));
+    let banks = read_banks("inputs/puzzle3.txt").unwrap();
+    assert_eq!(part1::<u64>(&banks), Ok(17158));
+    assert_eq!(part2::<u64>(&banks), Ok(170449335646486));
+}
+
+#[test]
+fn test_max_bank() {
+    let banks = read_banks("inputs/puzzle3.txt").unwrap();
+    for bank in &banks {
+        for digits in [1, 2, 5, 12, 19] {
+            let (val, indices) = max_bank::<u64>(bank, digits).unwrap();
+            assert_eq!(val, max_bank_slow(bank, digits));
+            assert_eq!(indices.len(), digits);
+            assert!(indices.windows(2).all(|w| w[0] < w[1]));
+        }
+    }
+    let bank = parse_bank("81819").unwrap();
+    assert_eq!(max_bank(&bank, 3), Ok((889u64, vec![0, 2, 4])));
+    let bank = parse_bank("555").unwrap();
+    assert_eq!(max_bank(&bank, 2), Ok((55u64, vec![0, 1])));
+
+    // a long bank, where the quadratic algorithm would be too slow for long selections
+    let mut rng = aoc2025::rng::Lcg::new(42);
+    let bank = Bank((0..300_000).map(|_| rng.below(10) as u8).collect());
+    assert_eq!(
+        max_bank::<u64>(&bank, 12).unwrap().0,
+        max_bank_slow(&bank, 12)
+    );
+    let indices = selection::max_indices(&bank, 200_000);
+    assert_eq!(indices.len(), 200_000);
+    assert!(indices.windows(2).all(|w| w[0] < w[1]));
+    // dropping a single digit, the best choice is the first one smaller than the next one
+    let dropped = (0..bank.len() - 1)
+        .find(|&i| bank[i] < bank[i + 1])
+        .unwrap();
+    let expected: Vec<usize> = (0..bank.len()).filter(|&i| i != dropped).collect();
+    assert_eq!(selection::max_indices(&bank, bank.len() - 1), expected);
+}
+
+#[test]
+fn test_long_selections() {
+    use num::BigUint;
+    let banks = read_banks("inputs/puzzle3.txt").unwrap();
+    let digits = 40;
+    assert_eq!(
+        total_joltage::<u64>(&banks, digits),
+        Err(BankError::Overflow)
+    );
+    let mut values = Vec::new();
+    for bank in &banks {
+        let (val, _) = max_bank::<BigUint>(bank, digits).unwrap();
+        assert_eq!(Ok(val.to_string()), max_bank_string(bank, digits));
+        values.push(val);
+    }
+    let total: BigUint = total_joltage(&banks, digits).unwrap();
+    assert_eq!(total, num::sum(values).unwrap());
+    assert!(total > BigUint::from(u64::MAX));
+    assert_eq!(
+        part2::<BigUint>(&banks).unwrap().to_string(),
+        "170449335646486"
+    );
+}
+
+#[test]
+fn test_validation() {
+    assert_eq!(parse_bank("987"), Ok(Bank(vec![9, 8, 7])));
+    assert_eq!(
+        parse_bank("98\r"),
+        Err(BankError::InvalidDigit {
+            position: 2,
+            found: '\r'
+        })
+    );
+    assert_eq!(
+        parse_bank("9x7").unwrap_err().to_string(),
+        "invalid digit 'x' at position 1"
+    );
+    let bank = parse_bank("987").unwrap();
+    assert_eq!(
+        max_bank::<u64>(&bank, 4),
+        Err(BankError::TooShort {
+            len: 3,
+            required: 4
+        })
+    );
+    assert_eq!(max_bank::<u64>(&bank, 3), Ok((987, vec![0, 1, 2])));
 }
 
 fn main() {
-    let banks = read_file("inputs/puzzle3.txt", "\n", parse_bank);
-    println!("Part 1: {}", part1(&banks));
-    println!("Part 2: {}", part2(&banks));
+    let banks = read_banks("inputs/puzzle3.txt").unwrap();
+    println!("Part 1: {}", part1::<u64>(&banks).unwrap());
+    println!("Part 2: {}", part2::<u64>(&banks).unwrap());
 }
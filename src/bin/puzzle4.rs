@@ -1,49 +1,251 @@
-use aoc2025::read_file;
-use std::cmp::min;
+use aoc2025::{
+    image::{Image, Rgb, heat},
+    read_file,
+    term::{Cell, Colour, Frame, FrameSink, Renderer},
+};
+use std::{fs, io, path::Path, str::FromStr, time::Duration};
+
+/// Set to true to watch the removal waves on the terminal.
+const VISUALIZE: bool = false;
+
+/// Set to a directory name to save an image of the roll map after each round.
+const EXPORT_DIR: Option<&str> = None;
+
+/// Set to a file name to save the map of the removal rounds, as text if the name ends in
+/// `.txt` and as an image otherwise.
+const EXPORT_MAP: Option<&str> = None;
+
+/// Set to true to solve with the bit-packed grid, which is faster and much smaller for very
+/// large maps.
+const BIT_PACKED: bool = false;
+
+/// The rule which determines whether a roll is free, in the format of `AccessRule::from_str`.
+/// The rule of the puzzle is `moore < 4`.
+const ACCESS_RULE: &str = "moore < 4";
 
 fn parse_row(row: &str) -> Vec<u8> {
     row.as_bytes().to_vec()
 }
 
-fn roll_is_free(maze: &Vec<Vec<u8>>, i: usize, j: usize) -> bool {
-    let mut count = 0u32;
-    let mini = if i == 0 { 0 } else { i-1 };
-    let maxi = min(i+1, maze.len()-1);
-    let minj = if j == 0 { 0 } else { j-1 };
-    let maxj = min(j+1, maze[i].len()-1);
-    for i1 in mini..=maxi {
-        for j1 in minj..=maxj {
-            if (i != i1 || j != j1) && maze[i1][j1] != b'.' {
-                count += 1;
-            }
+/// The cells around a roll whose rolls are counted to determine whether it is free.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Neighbourhood {
+    /// The four cells sharing a side with the roll.
+    VonNeumann,
+    /// The eight cells sharing a side or a corner with the roll.
+    Moore,
+    /// The cells at distance at most `r` from the roll in both directions, except the roll.
+    Radius(usize),
+    /// The cells at the given offsets (row, column) from the roll.
+    Custom(Vec<(isize, isize)>),
+}
+
+impl Neighbourhood {
+    /// Return the offsets (row, column) of the cells in the neighbourhood.
+    fn offsets(&self) -> Vec<(isize, isize)> {
+        let square = |r: isize| -> Vec<(isize, isize)> {
+            (-r..=r)
+                .flat_map(|di| (-r..=r).map(move |dj| (di, dj)))
+                .filter(|&d| d != (0, 0))
+                .collect()
+        };
+        match self {
+            Neighbourhood::VonNeumann => vec![(-1, 0), (0, -1), (0, 1), (1, 0)],
+            Neighbourhood::Moore => square(1),
+            Neighbourhood::Radius(r) => square(*r as isize),
+            Neighbourhood::Custom(offsets) => offsets.clone(),
+        }
+    }
+}
+
+/// How the number of neighbouring rolls is compared with the threshold.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Comparison {
+    /// A roll is free when it has fewer neighbouring rolls than the threshold.
+    Fewer,
+    /// A roll is free when it has more neighbouring rolls than the threshold.
+    More,
+}
+
+/// The rule which determines whether a roll is free and may be removed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct AccessRule {
+    offsets: Vec<(isize, isize)>,
+    threshold: u32,
+    comparison: Comparison,
+    /// Whether the maze wraps around its borders, like a torus.
+    wrap: bool,
+}
+
+impl AccessRule {
+    fn new(neighbourhood: Neighbourhood, threshold: u32, comparison: Comparison) -> Self {
+        Self {
+            offsets: neighbourhood.offsets(),
+            threshold,
+            comparison,
+            wrap: false,
+        }
+    }
+
+    /// The rule of the puzzle: a roll is free when fewer than four of its eight neighbours are
+    /// rolls.
+    #[cfg(test)]
+    fn puzzle() -> Self {
+        Self::new(Neighbourhood::Moore, 4, Comparison::Fewer)
+    }
+
+    /// Return the same rule on a maze which wraps around its borders. The maze must be
+    /// rectangular.
+    fn wrapping(self) -> Self {
+        Self { wrap: true, ..self }
+    }
+
+    /// Determine whether a roll with `count` neighbouring rolls is free.
+    fn is_free(&self, count: u32) -> bool {
+        match self.comparison {
+            Comparison::Fewer => count < self.threshold,
+            Comparison::More => count > self.threshold,
         }
     }
-    count < 4
+
+    /// Check that the rule may be applied to `maze`, that is that the maze is rectangular when
+    /// it wraps around, since otherwise the neighbours of the cells on the borders are not
+    /// well defined.
+    fn check(&self, maze: &[Vec<u8>]) {
+        let width = maze.first().map_or(0, |row| row.len());
+        assert!(
+            !self.wrap || maze.iter().all(|row| row.len() == width),
+            "a maze which wraps around must be rectangular"
+        );
+    }
+
+    /// Return the position of the cell at offset `(di, dj)` from the cell in row `i` and column
+    /// `j`, if it is in the maze. The maze must have passed `check`.
+    fn shift(
+        &self,
+        maze: &[Vec<u8>],
+        i: usize,
+        j: usize,
+        (di, dj): (isize, isize),
+    ) -> Option<(usize, usize)> {
+        if self.wrap {
+            let height = maze.len() as isize;
+            let width = maze[0].len() as isize;
+            let i1 = (i as isize + di).rem_euclid(height);
+            let j1 = (j as isize + dj).rem_euclid(width);
+            return Some((i1 as usize, j1 as usize));
+        }
+        let i1 = i.checked_add_signed(di)?;
+        let j1 = j.checked_add_signed(dj)?;
+        (j1 < maze.get(i1)?.len()).then_some((i1, j1))
+    }
+
+    /// Return the positions of the neighbours of the cell in row `i` and column `j`. A position
+    /// is repeated if it is reached from more than one offset, which may happen when the maze
+    /// wraps around.
+    fn neighbours<'a>(
+        &'a self,
+        maze: &'a [Vec<u8>],
+        i: usize,
+        j: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.offsets
+            .iter()
+            .filter_map(move |&d| self.shift(maze, i, j, d))
+    }
+
+    /// Return the positions of the cells which have the cell in row `i` and column `j` among
+    /// their neighbours. They are the same as the neighbours for symmetric neighbourhoods.
+    fn reverse_neighbours<'a>(
+        &'a self,
+        maze: &'a [Vec<u8>],
+        i: usize,
+        j: usize,
+    ) -> impl Iterator<Item = (usize, usize)> + 'a {
+        self.offsets
+            .iter()
+            .filter_map(move |&(di, dj)| self.shift(maze, i, j, (-di, -dj)))
+    }
+
+    /// Count the rolls among the neighbours of the cell in row `i` and column `j`.
+    fn count(&self, maze: &[Vec<u8>], i: usize, j: usize) -> u32 {
+        self.neighbours(maze, i, j)
+            .filter(|&(i1, j1)| (i1, j1) != (i, j) && maze[i1][j1] != b'.')
+            .count() as u32
+    }
+}
+
+/// Parse a rule such as `moore < 4` or `radius:2 > 10 wrap`: a neighbourhood, a comparison, a
+/// threshold and optionally `wrap`. The neighbourhood is one of `von-neumann`, `moore`,
+/// `radius:r` and `custom:` followed by the offsets, such as `custom:-1,0;0,2`.
+impl FromStr for AccessRule {
+    type Err = String;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let tokens: Vec<&str> = s.split_whitespace().collect();
+        let (neighbourhood, comparison, threshold, wrap) = match tokens[..] {
+            [n, c, t] => (n, c, t, false),
+            [n, c, t, "wrap"] => (n, c, t, true),
+            _ => return Err(format!("invalid rule {s:?}")),
+        };
+        let offset = |o: &str| -> Option<(isize, isize)> {
+            let (di, dj) = o.split_once(',')?;
+            Some((di.parse().ok()?, dj.parse().ok()?))
+        };
+        let neighbourhood = match neighbourhood.split_once(':') {
+            None if neighbourhood == "von-neumann" => Some(Neighbourhood::VonNeumann),
+            None if neighbourhood == "moore" => Some(Neighbourhood::Moore),
+            Some(("radius", r)) => r.parse().ok().map(Neighbourhood::Radius),
+            Some(("custom", offsets)) => offsets
+                .split(';')
+                .map(offset)
+                .collect::<Option<_>>()
+                .map(Neighbourhood::Custom),
+            _ => None,
+        }
+        .ok_or(format!("invalid neighbourhood {neighbourhood:?}"))?;
+        let comparison = match comparison {
+            "<" => Comparison::Fewer,
+            ">" => Comparison::More,
+            _ => return Err(format!("invalid comparison {comparison:?}")),
+        };
+        let threshold = threshold
+            .parse()
+            .map_err(|_| format!("invalid threshold {threshold:?}"))?;
+        let rule = AccessRule::new(neighbourhood, threshold, comparison);
+        Ok(if wrap { rule.wrapping() } else { rule })
+    }
 }
 
-fn mark_remove(maze: &mut Vec<Vec<u8>>, debug: bool) -> u32 {
+fn roll_is_free(maze: &[Vec<u8>], i: usize, j: usize, rule: &AccessRule) -> bool {
+    rule.is_free(rule.count(maze, i, j))
+}
+
+fn mark_remove(maze: &mut [Vec<u8>], rule: &AccessRule) -> u32 {
+    rule.check(maze);
     let mut count = 0;
-    for i in  0..maze.len() {
+    for i in 0..maze.len() {
         for j in 0..maze[i].len() {
-            if maze[i][j] == b'@' {
-                if roll_is_free(maze, i, j) {
-                    maze[i][j] = b'x';
-                    count += 1
-                }
-                if debug  {
-                    print!("{}", if roll_is_free(maze, i, j) { 'x' } else { '@' });
-                }
-            } else {
-                if debug { (print!(".")); }
+            if maze[i][j] == b'@' && roll_is_free(maze, i, j, rule) {
+                maze[i][j] = b'x';
+                count += 1
             }
         }
-        if  debug { println!(); }
     }
     count
 }
 
+/// Build a frame showing the maze, with the rolls marked for removal highlighted.
+fn maze_frame(maze: &[Vec<u8>], caption: String) -> Frame {
+    Frame::from_grid(caption, maze, |_i, _j, &x| match x {
+        b'@' => Cell::new('@', Colour::Green),
+        b'x' => Cell::new('x', Colour::Red),
+        _ => Cell::new('.', Colour::Default),
+    })
+}
+
 fn remove_marked(maze: &mut Vec<Vec<u8>>) {
-    for i in  0..maze.len() {
+    for i in 0..maze.len() {
         for j in 0..maze[i].len() {
             if maze[i][j] == b'x' {
                 maze[i][j] = b'.'
@@ -52,37 +254,599 @@ fn remove_marked(maze: &mut Vec<Vec<u8>>) {
     }
 }
 
-fn part1(maze: &Vec<Vec<u8>>, debug: bool) -> u32 {
-    let mut maze_copy: Vec<Vec<u8>> = maze.clone();
-    mark_remove(&mut maze_copy, debug)
+fn part1(maze: &[Vec<u8>], rule: &AccessRule, frames: Option<&mut dyn FrameSink>) -> u32 {
+    let mut maze_copy: Vec<Vec<u8>> = maze.to_vec();
+    let removed = mark_remove(&mut maze_copy, rule);
+    if let Some(sink) = frames {
+        sink.publish(maze_frame(&maze_copy, format!("{removed} free rolls")));
+    }
+    removed
+}
+
+/// Remove the free rolls round by round, as in part 2, and return the positions of the rolls
+/// removed in each round.
+///
+/// Instead of scanning the whole maze every round, we keep the number of neighbouring rolls of
+/// each roll, and only update the neighbours of the removed rolls: a roll is queued for the
+/// next round as soon as it becomes free. With the rule of the puzzle, this is the peeling
+/// algorithm which computes the 4-core of the graph of adjacent rolls, and the total work is
+/// linear in the size of the maze.
+fn removal_rounds(maze: &[Vec<u8>], rule: &AccessRule) -> Vec<Vec<(usize, usize)>> {
+    rule.check(maze);
+    let mut counts: Vec<Vec<u32>> = maze.iter().map(|row| vec![0; row.len()]).collect();
+    // whether a roll has been queued for removal, which happens only once
+    let mut queued: Vec<Vec<bool>> = maze.iter().map(|row| vec![false; row.len()]).collect();
+    let mut layer = Vec::new();
+    for i in 0..maze.len() {
+        for j in 0..maze[i].len() {
+            if maze[i][j] == b'@' {
+                counts[i][j] = rule.count(maze, i, j);
+                if rule.is_free(counts[i][j]) {
+                    queued[i][j] = true;
+                    layer.push((i, j));
+                }
+            }
+        }
+    }
+    let mut rounds = Vec::new();
+    while !layer.is_empty() {
+        let mut next = Vec::new();
+        for &(i, j) in &layer {
+            for (i1, j1) in rule.reverse_neighbours(maze, i, j) {
+                if maze[i1][j1] == b'@' && !queued[i1][j1] {
+                    counts[i1][j1] -= 1;
+                    if rule.is_free(counts[i1][j1]) {
+                        queued[i1][j1] = true;
+                        next.push((i1, j1));
+                    }
+                }
+            }
+        }
+        rounds.push(layer);
+        layer = next;
+    }
+    rounds
+}
+
+/// What happens to a cell of the maze while the free rolls are removed.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Fate {
+    /// The cell has no roll.
+    Empty,
+    /// The roll is removed in the given round, starting from 1.
+    Removed(usize),
+    /// The roll is never removed: it belongs to the stable core.
+    Core,
+}
+
+/// The round in which each roll of the maze is removed, as in part 2.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RemovalMap {
+    cells: Vec<Vec<Fate>>,
+    /// The number of rolls removed in each round.
+    counts: Vec<usize>,
 }
 
-fn part2(maze: &Vec<Vec<u8>>, debug: bool) -> u32 {
-    let mut maze_copy: Vec<Vec<u8>> = maze.clone();
+impl RemovalMap {
+    fn new(maze: &[Vec<u8>], rule: &AccessRule) -> Self {
+        let mut cells: Vec<Vec<Fate>> = maze
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|&x| if x == b'@' { Fate::Core } else { Fate::Empty })
+                    .collect()
+            })
+            .collect();
+        let rounds = removal_rounds(maze, rule);
+        for (round, removed) in rounds.iter().enumerate() {
+            for &(i, j) in removed {
+                cells[i][j] = Fate::Removed(round + 1);
+            }
+        }
+        let counts = rounds.iter().map(|removed| removed.len()).collect();
+        Self { cells, counts }
+    }
+
+    /// Return the number of rolls in the stable core.
+    fn core(&self) -> usize {
+        self.cells
+            .iter()
+            .flatten()
+            .filter(|&&fate| fate == Fate::Core)
+            .count()
+    }
+
+    /// Return the map as text. Empty cells are `.`, core rolls are `#`, and removed rolls show
+    /// their round as a digit or a letter (`1`-`9`, `a`-`z`, `A`-`Z`), or `+` after round 61.
+    fn to_text(&self) -> Vec<String> {
+        const SYMBOLS: &[u8] = b"123456789abcdefghijklmnopqrstuvwxyzABCDEFGHIJKLMNOPQRSTUVWXYZ";
+        self.cells
+            .iter()
+            .map(|row| {
+                row.iter()
+                    .map(|fate| match fate {
+                        Fate::Empty => '.',
+                        Fate::Core => '#',
+                        Fate::Removed(round) => SYMBOLS.get(round - 1).map_or('+', |&c| c as char),
+                    })
+                    .collect()
+            })
+            .collect()
+    }
+
+    /// Return the map as an image with `scale` pixels per cell. Removed rolls go from black,
+    /// for the first round, to white, for the last one, core rolls are blue.
+    fn to_image(&self, scale: usize) -> Image {
+        let last = self.counts.len().max(2) - 1;
+        Image::from_grid(&self.cells, scale, |_i, _j, fate| match fate {
+            Fate::Empty => Rgb::GREY,
+            Fate::Core => Rgb::BLUE,
+            Fate::Removed(round) => heat((round - 1) as f64 / last as f64),
+        })
+    }
+
+    /// Save the map to `path`, with the number of rolls removed in each round after the map
+    /// if the file is text.
+    fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        if path.extension().is_none_or(|e| e != "txt") {
+            return self.to_image(4).save(path);
+        }
+        let mut text = self.to_text().join("\n");
+        text.push_str("\n\n");
+        for (round, count) in self.counts.iter().enumerate() {
+            text.push_str(&format!("round {}: {count} rolls removed\n", round + 1));
+        }
+        text.push_str(&format!("core: {} rolls\n", self.core()));
+        fs::write(path, text)
+    }
+}
+
+/// Same as `part2`, rescanning the whole maze every round.
+#[cfg(test)]
+fn part2_slow(maze: &[Vec<u8>], rule: &AccessRule) -> u32 {
+    let mut maze_copy: Vec<Vec<u8>> = maze.to_vec();
     let mut count = 0;
     loop {
-        if debug { println!("---- STEP ----") }
-        let removed = mark_remove(&mut maze_copy, debug);
-        if removed == 0 { break; }
+        let removed = mark_remove(&mut maze_copy, rule);
+        if removed == 0 {
+            break;
+        }
         count += removed;
         remove_marked(&mut maze_copy);
     }
     count
 }
 
+fn part2(maze: &[Vec<u8>], rule: &AccessRule, frames: Option<&mut dyn FrameSink>) -> u32 {
+    let rounds = removal_rounds(maze, rule);
+    if let Some(sink) = frames {
+        // replay the rounds, showing the rolls removed in each of them
+        let mut maze_copy: Vec<Vec<u8>> = maze.to_vec();
+        for (round, removed) in rounds.iter().enumerate() {
+            for &(i, j) in removed {
+                maze_copy[i][j] = b'x';
+            }
+            let caption = format!("round {}: {} rolls removed", round + 1, removed.len());
+            sink.publish(maze_frame(&maze_copy, caption));
+            remove_marked(&mut maze_copy);
+        }
+        let caption = format!("round {}: 0 rolls removed", rounds.len() + 1);
+        sink.publish(maze_frame(&maze_copy, caption));
+    }
+    rounds.iter().map(|removed| removed.len() as u32).sum()
+}
+
+/// A maze stored with one bit per cell, set for the cells with a roll. Each row takes `words`
+/// 64-bit words, with column `j` in bit `j % 64` of word `j / 64`, and the bits after the end
+/// of the row are always clear.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct BitGrid {
+    height: usize,
+    width: usize,
+    words: usize,
+    bits: Vec<u64>,
+}
+
+impl BitGrid {
+    fn new(height: usize, width: usize) -> Self {
+        let words = width.div_ceil(64);
+        Self {
+            height,
+            width,
+            words,
+            bits: vec![0; height * words],
+        }
+    }
+
+    /// Build the grid from the rows of the maze, as wide as the longest one.
+    #[cfg(test)]
+    fn from_maze(maze: &[Vec<u8>]) -> Self {
+        let width = maze.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut grid = Self::new(maze.len(), width);
+        for (i, row) in maze.iter().enumerate() {
+            for (j, &x) in row.iter().enumerate() {
+                if x == b'@' {
+                    grid.set(i, j);
+                }
+            }
+        }
+        grid
+    }
+
+    /// Build the grid directly from the text of the maze, without storing it a byte per cell.
+    fn parse(input: &str) -> Self {
+        let lines: Vec<&str> = input.lines().filter(|line| !line.is_empty()).collect();
+        let width = lines.iter().map(|line| line.len()).max().unwrap_or(0);
+        let mut grid = Self::new(lines.len(), width);
+        for (i, line) in lines.iter().enumerate() {
+            for (j, &x) in line.as_bytes().iter().enumerate() {
+                if x == b'@' {
+                    grid.set(i, j);
+                }
+            }
+        }
+        grid
+    }
+
+    fn row(&self, i: usize) -> &[u64] {
+        &self.bits[i * self.words..(i + 1) * self.words]
+    }
+
+    fn set(&mut self, i: usize, j: usize) {
+        self.bits[i * self.words + j / 64] |= 1 << (j % 64);
+    }
+
+    #[cfg(test)]
+    fn get(&self, i: usize, j: usize) -> bool {
+        self.bits[i * self.words + j / 64] >> (j % 64) & 1 == 1
+    }
+
+    /// Return the number of rolls.
+    fn count(&self) -> u64 {
+        self.bits.iter().map(|w| w.count_ones() as u64).sum()
+    }
+
+    /// Remove the rolls which are set in `other`.
+    fn remove(&mut self, other: &BitGrid) {
+        for (w, o) in self.bits.iter_mut().zip(&other.bits) {
+            *w &= !o;
+        }
+    }
+
+    /// Write in `out` the row `i` shifted so that column `j` holds column `j + dj`, which is
+    /// empty when outside the row.
+    fn shift_row(&self, i: usize, dj: isize, out: &mut [u64]) {
+        let row = self.row(i);
+        let word = |w: isize| {
+            if 0 <= w && w < row.len() as isize {
+                row[w as usize]
+            } else {
+                0
+            }
+        };
+        let (q, r) = (dj.div_euclid(64), dj.rem_euclid(64) as u32);
+        for (w, x) in out.iter_mut().enumerate() {
+            let w = w as isize + q;
+            // the high bits of the following word fill in the top of the shifted one
+            *x = word(w) >> r | (word(w + 1) << (63 - r) << 1);
+        }
+    }
+
+    /// Return the grid of the free rolls. For each row, the numbers of neighbouring rolls are
+    /// kept bit-sliced: plane `k` holds bit `k` of the count of each cell, so that a neighbour
+    /// is added to 64 cells at once with a ripple-carry addition over the planes.
+    fn free(&self, rule: &AccessRule) -> BitGrid {
+        assert!(!rule.wrap, "the bit-packed grid does not wrap around");
+        let offsets: Vec<(isize, isize)> = rule
+            .offsets
+            .iter()
+            .copied()
+            .filter(|&d| d != (0, 0))
+            .collect();
+        let mut planes =
+            vec![vec![0u64; self.words]; (usize::BITS - offsets.len().leading_zeros()) as usize];
+        let mut shifted = vec![0u64; self.words];
+        let mut free = BitGrid::new(self.height, self.width);
+        for i in 0..self.height {
+            planes.iter_mut().for_each(|plane| plane.fill(0));
+            for &(di, dj) in &offsets {
+                let Some(i1) = i.checked_add_signed(di).filter(|&i1| i1 < self.height) else {
+                    continue;
+                };
+                self.shift_row(i1, dj, &mut shifted);
+                for (w, &x) in shifted.iter().enumerate() {
+                    let mut carry = x;
+                    for plane in planes.iter_mut() {
+                        let sum = plane[w] ^ carry;
+                        carry &= plane[w];
+                        plane[w] = sum;
+                    }
+                }
+            }
+            for (w, &rolls) in self.row(i).iter().enumerate() {
+                free.bits[i * self.words + w] = rolls & compare(&planes, w, rule);
+            }
+        }
+        free
+    }
+}
+
+/// Return the mask of the cells in word `w` whose bit-sliced count in `planes` makes a roll
+/// free, comparing the counts with the threshold from the most significant bit down.
+fn compare(planes: &[Vec<u64>], w: usize, rule: &AccessRule) -> u64 {
+    let (mut less, mut greater, mut equal) = (0u64, 0u64, !0u64);
+    if rule.threshold.checked_shr(planes.len() as u32).unwrap_or(0) != 0 {
+        // the threshold is larger than any count
+        (less, equal) = (!0, 0);
+    }
+    for (k, plane) in planes.iter().enumerate().rev() {
+        let bit = plane[w];
+        if rule.threshold >> k & 1 == 1 {
+            less |= equal & !bit;
+            equal &= bit;
+        } else {
+            greater |= equal & bit;
+            equal &= !bit;
+        }
+    }
+    match rule.comparison {
+        Comparison::Fewer => less,
+        Comparison::More => greater,
+    }
+}
+
+/// Same as `part1`, on a bit-packed grid.
+fn part1_bits(grid: &BitGrid, rule: &AccessRule) -> u64 {
+    grid.free(rule).count()
+}
+
+/// Remove the free rolls round by round from a bit-packed grid, and return the number of rolls
+/// removed in each round.
+fn removal_counts_bits(grid: &BitGrid, rule: &AccessRule) -> Vec<u64> {
+    let mut grid = grid.clone();
+    let mut counts = Vec::new();
+    loop {
+        let free = grid.free(rule);
+        let removed = free.count();
+        if removed == 0 {
+            return counts;
+        }
+        counts.push(removed);
+        grid.remove(&free);
+    }
+}
+
+/// Same as `part2`, on a bit-packed grid.
+fn part2_bits(grid: &BitGrid, rule: &AccessRule) -> u64 {
+    removal_counts_bits(grid, rule).iter().sum()
+}
+
+/// Save an image of the roll map for each round of part 2 in the directory `dir`. Rolls
+/// which are removed in the round are red, the other ones are green.
+fn export_rounds(maze: &[Vec<u8>], rule: &AccessRule, dir: &str) -> io::Result<()> {
+    let map = RemovalMap::new(maze, rule);
+    // the last round removes nothing and shows the stable core
+    for round in 1..=map.counts.len() + 1 {
+        let image = Image::from_grid(&map.cells, 4, |_i, _j, &fate| match fate {
+            Fate::Removed(r) if r == round => Rgb::RED,
+            Fate::Removed(r) if r > round => Rgb::GREEN,
+            Fate::Core => Rgb::GREEN,
+            _ => Rgb::WHITE,
+        });
+        image.save(format!("{dir}/puzzle4_round{round:03}.png"))?;
+    }
+    Ok(())
+}
 
 #[test]
 fn test() {
     let maze = read_file("inputs/puzzle4_example.txt", "\n", parse_row);
-    assert_eq!(part1(&maze, false), 13);
-    assert_eq!(part2(&maze, false), 43);
+    assert_eq!(part1(&maze, &AccessRule::puzzle(), None), 13);
+    let mut frames = Vec::new();
+    assert_eq!(part2(&maze, &AccessRule::puzzle(), Some(&mut frames)), 43);
+    assert_eq!(frames.len(), 10);
+    assert_eq!(frames[0].to_plain()[0], "..xx.xx@x.");
+    assert!(
+        frames
+            .last()
+            .unwrap()
+            .to_plain()
+            .iter()
+            .all(|row| !row.contains('x'))
+    );
     let maze = read_file("inputs/puzzle4.txt", "\n", parse_row);
-    assert_eq!(part1(&maze, false), 1569);
-    assert_eq!(part2(&maze, false), 9280);
+    assert_eq!(part1(&maze, &AccessRule::puzzle(), None), 1569);
+    assert_eq!(part2(&maze, &AccessRule::puzzle(), None), 9280);
+}
+
+#[test]
+fn test_removal_rounds() {
+    for filename in ["inputs/puzzle4_example.txt", "inputs/puzzle4.txt"] {
+        let maze = read_file(filename, "\n", parse_row);
+        assert_eq!(
+            part2(&maze, &AccessRule::puzzle(), None),
+            part2_slow(&maze, &AccessRule::puzzle())
+        );
+        let rules = [
+            AccessRule::puzzle(),
+            AccessRule::puzzle().wrapping(),
+            AccessRule::new(Neighbourhood::VonNeumann, 2, Comparison::Fewer),
+            AccessRule::new(Neighbourhood::Radius(2), 12, Comparison::Fewer).wrapping(),
+            AccessRule::new(Neighbourhood::Moore, 5, Comparison::More),
+            AccessRule::new(
+                Neighbourhood::Custom(vec![(0, 1), (0, 2), (1, 1)]),
+                2,
+                Comparison::Fewer,
+            ),
+            AccessRule::new(
+                Neighbourhood::Custom(vec![(-1, 3), (2, 0)]),
+                1,
+                Comparison::Fewer,
+            )
+            .wrapping(),
+        ];
+        for rule in &rules {
+            // the rolls removed in each round are those marked by the round-based algorithm
+            let mut maze_copy = maze.clone();
+            for removed in removal_rounds(&maze, rule) {
+                assert_eq!(mark_remove(&mut maze_copy, rule), removed.len() as u32);
+                assert!(removed.iter().all(|&(i, j)| maze_copy[i][j] == b'x'));
+                remove_marked(&mut maze_copy);
+            }
+            assert_eq!(mark_remove(&mut maze_copy, rule), 0);
+        }
+    }
+}
+
+#[test]
+fn test_removal_map() {
+    let maze = read_file("inputs/puzzle4_example.txt", "\n", parse_row);
+    let map = RemovalMap::new(&maze, &AccessRule::puzzle());
+    assert_eq!(map.counts, vec![13, 12, 7, 5, 2, 1, 1, 1, 1]);
+    let text = map.to_text();
+    assert_eq!(text[0], "..11.1121.");
+    let rolls = maze.iter().flatten().filter(|&&x| x == b'@').count();
+    assert_eq!(map.core(), rolls - 43);
+    assert_eq!(text.concat().matches('#').count(), map.core());
+    let image = map.to_image(2);
+    assert_eq!((image.width(), image.height()), (20, 20));
+    assert_eq!(image.get(0, 0), Rgb::GREY);
+    assert_eq!(image.get(4, 0), Rgb::BLACK);
+}
+
+#[test]
+fn test_bit_grid() {
+    for filename in ["inputs/puzzle4_example.txt", "inputs/puzzle4.txt"] {
+        let maze = read_file(filename, "\n", parse_row);
+        let grid = BitGrid::parse(&fs::read_to_string(filename).unwrap());
+        assert_eq!(grid, BitGrid::from_maze(&maze));
+        assert_eq!(
+            part1_bits(&grid, &AccessRule::puzzle()),
+            part1(&maze, &AccessRule::puzzle(), None) as u64
+        );
+        assert_eq!(
+            part2_bits(&grid, &AccessRule::puzzle()),
+            part2(&maze, &AccessRule::puzzle(), None) as u64
+        );
+        let rules = [
+            AccessRule::new(Neighbourhood::VonNeumann, 2, Comparison::Fewer),
+            AccessRule::new(Neighbourhood::Radius(2), 12, Comparison::Fewer),
+            AccessRule::new(Neighbourhood::Moore, 5, Comparison::More),
+            AccessRule::new(Neighbourhood::Moore, 9, Comparison::Fewer),
+            AccessRule::new(
+                Neighbourhood::Custom(vec![(0, 0), (0, 1), (0, 2), (1, 1)]),
+                2,
+                Comparison::Fewer,
+            ),
+            AccessRule::new(
+                Neighbourhood::Custom(vec![(-1, 70), (2, -65), (0, -64)]),
+                1,
+                Comparison::Fewer,
+            ),
+        ];
+        for rule in &rules {
+            let counts: Vec<u64> = removal_rounds(&maze, rule)
+                .iter()
+                .map(|r| r.len() as u64)
+                .collect();
+            assert_eq!(removal_counts_bits(&grid, rule), counts, "{rule:?}");
+        }
+    }
+    // rows longer than a word
+    let maze: Vec<Vec<u8>> = (0..5)
+        .map(|i| {
+            (0..150)
+                .map(|j| if (i * 7 + j * 3) % 5 < 3 { b'@' } else { b'.' })
+                .collect()
+        })
+        .collect();
+    let grid = BitGrid::from_maze(&maze);
+    assert!(grid.get(0, 0) && !grid.get(0, 1));
+    assert_eq!(
+        part1_bits(&grid, &AccessRule::puzzle()),
+        part1(&maze, &AccessRule::puzzle(), None) as u64
+    );
+    assert_eq!(
+        part2_bits(&grid, &AccessRule::puzzle()),
+        part2(&maze, &AccessRule::puzzle(), None) as u64
+    );
+}
+
+#[test]
+fn test_access_rule() {
+    let maze = read_file("inputs/puzzle4_example.txt", "\n", parse_row);
+    assert_eq!(
+        Neighbourhood::Radius(1).offsets(),
+        Neighbourhood::Moore.offsets()
+    );
+    assert_eq!(Neighbourhood::Radius(2).offsets().len(), 24);
+    // on a torus, the corner at the top left is next to the other three corners
+    let rule = AccessRule::new(Neighbourhood::Moore, 4, Comparison::Fewer).wrapping();
+    let last = (maze.len() - 1, maze[0].len() - 1);
+    let neighbours: Vec<_> = rule.neighbours(&maze, 0, 0).collect();
+    assert!(neighbours.contains(&last));
+    assert!(neighbours.contains(&(0, last.1)));
+    assert_eq!(AccessRule::puzzle().neighbours(&maze, 0, 0).count(), 3);
+
+    assert_eq!(ACCESS_RULE.parse(), Ok(AccessRule::puzzle()));
+    assert_eq!(
+        "radius:2 > 10 wrap".parse(),
+        Ok(AccessRule::new(Neighbourhood::Radius(2), 10, Comparison::More).wrapping())
+    );
+    assert_eq!(
+        "von-neumann < 2".parse(),
+        Ok(AccessRule::new(
+            Neighbourhood::VonNeumann,
+            2,
+            Comparison::Fewer
+        ))
+    );
+    assert_eq!(
+        "custom:-1,0;0,2 < 1".parse(),
+        Ok(AccessRule::new(
+            Neighbourhood::Custom(vec![(-1, 0), (0, 2)]),
+            1,
+            Comparison::Fewer
+        ))
+    );
+    for invalid in [
+        "moore",
+        "moore <= 4",
+        "moore < x",
+        "hex < 2",
+        "custom:1 < 2",
+        "moore < 4 torus",
+    ] {
+        assert!(invalid.parse::<AccessRule>().is_err(), "{invalid}");
+    }
+}
+
+#[test]
+#[should_panic(expected = "rectangular")]
+fn test_ragged_wrap() {
+    let maze = vec![b"@@@".to_vec(), b"@@".to_vec()];
+    removal_rounds(&maze, &AccessRule::puzzle().wrapping());
 }
 
 fn main() {
+    let rule: AccessRule = ACCESS_RULE.parse().unwrap();
+    if BIT_PACKED {
+        let grid = BitGrid::parse(&fs::read_to_string("inputs/puzzle4.txt").unwrap());
+        println!("Part 1: {}", part1_bits(&grid, &rule));
+        println!("Part 2: {}", part2_bits(&grid, &rule));
+        return;
+    }
     let maze = read_file("inputs/puzzle4.txt", "\n", parse_row);
-    println!("Part 1: {}", part1(&maze, false));
-    println!("Part 2: {}", part2(&maze, false));
+    // the renderer locks the standard input, so it is only created when needed
+    let mut renderer = VISUALIZE.then(|| Renderer::stdout().with_delay(Duration::from_millis(500)));
+    let frames = renderer.as_mut().map(|r| r as &mut dyn FrameSink);
+    println!("Part 1: {}", part1(&maze, &rule, None));
+    println!("Part 2: {}", part2(&maze, &rule, frames));
+    if let Some(dir) = EXPORT_DIR {
+        export_rounds(&maze, &rule, dir).unwrap();
+    }
+    if let Some(path) = EXPORT_MAP {
+        RemovalMap::new(&maze, &rule).save(path).unwrap();
+    }
 }
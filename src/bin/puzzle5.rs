@@ -1,4 +1,7 @@
-use aoc2025::read_file_split;
+use aoc2025::{interval::IntervalSet, read_file_split};
+
+/// Set to `true` to print which ranges contain each available ID, and what each range covers.
+const REPORT: bool = false;
 
 fn parse_range_id(row: &str) -> (u64, u64) {
     let (start, end) = row
@@ -12,40 +15,134 @@ fn parse_id(row: &str) -> u64 {
     row.parse().unwrap()
 }
 
-fn is_safe(id: u64, safe_ids: &Vec<(u64, u64)>) -> bool {
-    safe_ids
+fn part1(safe_ids: &[(u64, u64)], available_ids: &[u64]) -> u64 {
+    let safe = IntervalSet::new(safe_ids);
+    available_ids
         .iter()
-        .any(|(l, r)| *l <= id && id <= *r)
+        .map(|id| safe.contains(*id) as u64)
+        .sum()
 }
 
-fn part1(safe_ids: &Vec<(u64, u64)>, available_ids: &Vec<u64>) -> u64 {
-    available_ids
+/// Count the IDs in any of the ranges. The ranges may cover all the 2^64 IDs, which do not fit
+/// in a `u64`.
+fn part2(safe_ids: &[(u64, u64)]) -> u128 {
+    // once overlapping ranges are merged, no ID is counted twice
+    IntervalSet::new(safe_ids).cardinality()
+}
+
+/// What a range of the database covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RangeReport {
+    /// The line of the range in the input, starting from 1.
+    line: usize,
+    range: (u64, u64),
+    /// The number of IDs in the range.
+    size: u128,
+    /// The number of available IDs in the range.
+    available: usize,
+    /// Whether the other ranges cover all the IDs of this one. Each redundant range can be
+    /// removed on its own, but not always together with the other ones: of two equal ranges,
+    /// both are redundant.
+    redundant: bool,
+}
+
+/// Which ranges contain each available ID, and what each range covers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct Attribution {
+    /// Each available ID with the lines of the ranges which contain it.
+    ids: Vec<(u64, Vec<usize>)>,
+    ranges: Vec<RangeReport>,
+}
+
+/// Return the IDs which are in exactly one of the ranges. The coverage of the ranges changes
+/// only at their extremes, which are swept in order, one past the end for the last ID of a
+/// range, hence the `u128`.
+fn uniquely_covered(safe_ids: &[(u64, u64)]) -> IntervalSet<u64> {
+    let mut events: Vec<(u128, i32)> = safe_ids
         .iter()
-        .map(|id| is_safe(*id, safe_ids) as u64)
-        .sum()
+        .filter(|(l, r)| l <= r)
+        .flat_map(|&(l, r)| [(l as u128, 1), (r as u128 + 1, -1)])
+        .collect();
+    events.sort();
+    let mut unique = Vec::new();
+    let mut coverage = 0;
+    for (k, &(x, delta)) in events.iter().enumerate() {
+        coverage += delta;
+        match events.get(k + 1) {
+            Some(&(next, _)) if coverage == 1 && next > x => {
+                unique.push((x as u64, (next - 1) as u64));
+            }
+            _ => {}
+        }
+    }
+    IntervalSet::new(&unique)
 }
 
-fn part2(safe_ids: &Vec<(u64, u64)>) -> u64 {
-    let mut safe_ids_sorted = safe_ids.clone();
-    safe_ids_sorted.sort_by_key(|(l, _r)| *l);
-    let mut count = 0;
-    let mut prev_r = 0;
-    for (l, r) in safe_ids_sorted {
-        if prev_r < l {
-            count += r - l + 1;
-            prev_r = r
-        } else if prev_r < r {
-            // we don't have +1 since prev_r has been counted already
-            count += r - prev_r;
-            prev_r = r
+fn attribution(safe_ids: &[(u64, u64)], available_ids: &[u64]) -> Attribution {
+    let mut available = vec![0; safe_ids.len()];
+    let ids: Vec<(u64, Vec<usize>)> = available_ids
+        .iter()
+        .map(|&id| {
+            let lines: Vec<usize> = (1..=safe_ids.len())
+                .filter(|&line| safe_ids[line - 1].0 <= id && id <= safe_ids[line - 1].1)
+                .collect();
+            for &line in &lines {
+                available[line - 1] += 1;
+            }
+            (id, lines)
+        })
+        .collect();
+    // a range is redundant unless some of its IDs are in no other range
+    let unique = uniquely_covered(safe_ids);
+    let ranges = safe_ids
+        .iter()
+        .zip(available)
+        .enumerate()
+        .map(|(i, (&range, available))| RangeReport {
+            line: i + 1,
+            range,
+            size: IntervalSet::new(&[range]).cardinality(),
+            available,
+            redundant: !unique.intersects(range),
+        })
+        .collect();
+    Attribution { ids, ranges }
+}
+
+fn print_report(report: &Attribution) {
+    let fresh = report
+        .ids
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .count();
+    println!("{fresh} of {} available IDs are fresh", report.ids.len());
+    for (id, lines) in &report.ids {
+        let lines: Vec<String> = lines.iter().map(|line| line.to_string()).collect();
+        if lines.is_empty() {
+            println!("  {id}: spoiled");
+        } else {
+            println!("  {id}: lines {}", lines.join(", "));
         }
     }
-    count
+    let redundant = report.ranges.iter().filter(|r| r.redundant).count();
+    println!("{} ranges, {redundant} redundant", report.ranges.len());
+    for r in &report.ranges {
+        println!(
+            "  line {}: {}-{}, {} IDs, {} available{}",
+            r.line,
+            r.range.0,
+            r.range.1,
+            r.size,
+            r.available,
+            if r.redundant { ", redundant" } else { "" }
+        );
+    }
 }
 
 #[test]
 fn test() {
-    let (safe_ids, available_ids) = read_file_split("inputs/puzzle5_example.txt", parse_range_id, parse_id);
+    let (safe_ids, available_ids) =
+        read_file_split("inputs/puzzle5_example.txt", parse_range_id, parse_id);
     assert_eq!(part1(&safe_ids, &available_ids), 3);
     assert_eq!(part2(&safe_ids), 14);
     let (safe_ids, available_ids) = read_file_split("inputs/puzzle5.txt", parse_range_id, parse_id);
@@ -53,8 +150,86 @@ fn test() {
     assert_eq!(part2(&safe_ids), 344378119285354);
 }
 
+#[test]
+fn test_limits() {
+    assert_eq!(part2(&[(0, 0)]), 1);
+    assert_eq!(part2(&[(0, 0), (0, 0), (2, 2)]), 2);
+    assert_eq!(part2(&[(0, 0), (1, 1)]), 2);
+    assert_eq!(part2(&[(5, 4)]), 0);
+    assert_eq!(part2(&[]), 0);
+    assert_eq!(part2(&[(u64::MAX, u64::MAX)]), 1);
+    assert_eq!(part2(&[(u64::MAX - 9, u64::MAX), (0, 9)]), 20);
+    assert_eq!(part2(&[(0, u64::MAX)]), 1 << 64);
+    assert_eq!(part2(&[(1, u64::MAX), (0, 0)]), 1 << 64);
+    assert_eq!(part1(&[(0, 0), (u64::MAX, u64::MAX)], &[0, 1, u64::MAX]), 2);
+    assert_eq!(parse_range_id("0-18446744073709551615"), (0, u64::MAX));
+}
+
+#[test]
+fn test_attribution() {
+    let (safe_ids, available_ids) =
+        read_file_split("inputs/puzzle5_example.txt", parse_range_id, parse_id);
+    let report = attribution(&safe_ids, &available_ids);
+    assert_eq!(
+        report.ids,
+        vec![
+            (1, vec![]),
+            (5, vec![1]),
+            (8, vec![]),
+            (11, vec![2]),
+            (17, vec![3, 4]),
+            (32, vec![])
+        ]
+    );
+    let sizes: Vec<u128> = report.ranges.iter().map(|r| r.size).collect();
+    assert_eq!(sizes, vec![3, 5, 5, 7]);
+    assert!(
+        report
+            .ranges
+            .iter()
+            .all(|r| r.available == 1 && !r.redundant)
+    );
+
+    let report = attribution(
+        &[(3, 5), (1, 10), (4, 4), (4, 4), (9, 12), (6, 5)],
+        &[4, 11],
+    );
+    assert_eq!(report.ids, vec![(4, vec![1, 2, 3, 4]), (11, vec![5])]);
+    let redundant: Vec<bool> = report.ranges.iter().map(|r| r.redundant).collect();
+    assert_eq!(redundant, vec![true, false, true, true, false, true]);
+    assert_eq!(report.ranges[5].size, 0);
+    let (safe_ids, available_ids) = read_file_split("inputs/puzzle5.txt", parse_range_id, parse_id);
+    let report = attribution(&safe_ids, &available_ids);
+    let fresh = report
+        .ids
+        .iter()
+        .filter(|(_, lines)| !lines.is_empty())
+        .count();
+    assert_eq!(fresh as u64, part1(&safe_ids, &available_ids));
+    for r in &report.ranges {
+        let others: Vec<(u64, u64)> = (0..safe_ids.len())
+            .filter(|&j| j != r.line - 1)
+            .map(|j| safe_ids[j])
+            .collect();
+        let own = IntervalSet::new(&[r.range]);
+        assert_eq!(
+            r.redundant,
+            own.difference(&IntervalSet::new(&others)).is_empty()
+        );
+    }
+    // removing a single redundant range does not change the fresh IDs
+    if let Some(r) = report.ranges.iter().find(|r| r.redundant) {
+        let mut kept = safe_ids.clone();
+        kept.remove(r.line - 1);
+        assert_eq!(part2(&kept), part2(&safe_ids));
+    }
+}
+
 fn main() {
     let (safe_ids, available_ids) = read_file_split("inputs/puzzle5.txt", parse_range_id, parse_id);
     println!("Part 1: {}", part1(&safe_ids, &available_ids));
     println!("Part 2: {}", part2(&safe_ids));
+    if REPORT {
+        print_report(&attribution(&safe_ids, &available_ids));
+    }
 }
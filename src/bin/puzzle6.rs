@@ -1,40 +1,44 @@
-use aoc2025::read_file;
+use aoc2025::{
+    num::{Num, Overflow, from_decimal},
+    read_file,
+};
 
-fn parse_table(table: &Vec<String>) -> (Vec<Vec<u64>>, Vec<&str>) {
+fn parse_table<N: Num>(table: &[String]) -> Result<(Vec<Vec<N>>, Vec<&str>), Overflow> {
     let values = table[0..table.len() - 1]
         .iter()
-        .map(|row| row.split_whitespace().map(|v| v.parse().unwrap()).collect())
-        .collect();
+        .map(|row| row.split_whitespace().map(from_decimal).collect())
+        .collect::<Result<_, _>>()?;
     let operators = table.last().unwrap().split_whitespace().collect();
-    (values, operators)
+    Ok((values, operators))
 }
 
-fn part1(content: &Vec<String>) -> u64 {
-    let (values, operators) = parse_table(content);
-    let mut result = 0;
+fn part1<N: Num>(content: &[String]) -> Result<N, Overflow> {
+    let (values, operators) = parse_table::<N>(content)?;
+    let mut result = N::zero();
     for i in 0..operators.len() {
-        let mut problem_result = (operators[i] == "*") as u64;
+        let mut problem_result = N::from_u64((operators[i] == "*") as u64);
         for j in 0..values.len() {
+            let value = &values[j][i];
             if operators[i] == "+" {
-                problem_result += values[j][i]
+                problem_result = problem_result.add(value)?
             } else {
-                problem_result *= values[j][i]
+                problem_result = problem_result.mul(value)?
             }
         }
-        result += problem_result
+        result = result.add(&problem_result)?
     }
-    result
+    Ok(result)
 }
 
-fn part2(content: &Vec<String>) -> u64 {
+fn part2<N: Num>(content: &[String]) -> Result<N, Overflow> {
     let raw = content
         .iter()
         .map(|row| row.as_bytes())
         .collect::<Vec<&[u8]>>();
-    let mut result = 0; // the puzzle result
+    let mut result = N::zero(); // the puzzle result
     let mut opcol = true; // true if we are on the first column of a problem
     let mut op = b' '; // the operators to use for the current problem
-    let mut problem_result = 0; // the result of the current problem
+    let mut problem_result = N::zero(); // the result of the current problem
     let ncols = raw.iter().map(|r| r.len()).max().unwrap();
     // we also cycle on the non-existent column ncol so that we can treat
     // the last problem like all the others
@@ -42,42 +46,63 @@ fn part2(content: &Vec<String>) -> u64 {
         if opcol {
             op = raw[raw.len() - 1][j];
             opcol = false;
-            problem_result = (op == b'*') as u64; // unit of operation op
+            problem_result = N::from_u64((op == b'*') as u64); // unit of operation op
         }
-        let mut column: u64 = 0; // the value of the current column
-        for i in 0..raw.len() - 1 {
-            if j < raw[i].len() && raw[i][j] != b' ' {
-                column = column * 10 + ((raw[i][j] - b'0') as u64);
+        let mut column = N::zero(); // the value of the current column
+        for row in &raw[..raw.len() - 1] {
+            if j < row.len() && row[j] != b' ' {
+                let digit = N::from_u64((row[j] - b'0') as u64);
+                column = column.mul(&N::from_u64(10))?.add(&digit)?;
             }
         }
-        if column == 0 {
+        if column == N::zero() {
             // problem terminated
             opcol = true;
-            result += problem_result
+            result = result.add(&problem_result)?
         } else {
             // still in the middle of a problem
             if op == b'*' {
-                problem_result *= column
+                problem_result = problem_result.mul(&column)?
             } else {
-                problem_result += column
+                problem_result = problem_result.add(&column)?
             };
         }
     }
-    result
+    Ok(result)
 }
 
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle6_example.txt", "\n", str::to_string);
-    assert_eq!(part1(&content), 4277556);
-    assert_eq!(part2(&content), 3263827);
+    assert_eq!(part1::<u64>(&content), Ok(4277556));
+    assert_eq!(part2::<u64>(&content), Ok(3263827));
     let content = read_file("inputs/puzzle6.txt", "\n", str::to_string);
-    assert_eq!(part1(&content), 6503327062445);
-    assert_eq!(part2(&content), 9640641878593);
+    assert_eq!(part1::<u64>(&content), Ok(6503327062445));
+    assert_eq!(part2::<u64>(&content), Ok(9640641878593));
+    let content = vec![
+        "4294967296".to_string(),
+        "4294967296".to_string(),
+        "*".to_string(),
+    ];
+    assert_eq!(part1::<u64>(&content), Err(Overflow));
+    assert_eq!(part1::<u128>(&content), Ok(1 << 64));
+    // a column with twenty digits does not fit in a u64
+    let mut content = vec!["9".to_string(); 20];
+    content.push("+".to_string());
+    assert_eq!(part2::<u64>(&content), Err(Overflow));
+    assert_eq!(part2::<u128>(&content), Ok(10u128.pow(20) - 1));
+    // an operand with twenty digits does not fit in a u64
+    let content = vec![
+        "99999999999999999999 3".to_string(),
+        "1 4".to_string(),
+        "+ *".to_string(),
+    ];
+    assert_eq!(part1::<u64>(&content), Err(Overflow));
+    assert_eq!(part1::<u128>(&content), Ok(10u128.pow(20) + 12));
 }
 
 fn main() {
     let content = read_file("inputs/puzzle6.txt", "\n", str::to_string);
-    println!("Part 1: {}", part1(&content));
-    println!("Part 2: {}", part2(&content));
+    println!("Part 1: {}", part1::<u64>(&content).unwrap());
+    println!("Part 2: {}", part2::<u64>(&content).unwrap());
 }
@@ -1,5 +1,17 @@
-use aoc2025::read_file;
-use std::collections::hash_set::HashSet;
+use aoc2025::{
+    image::{Image, Rgb, heat},
+    memo::Memo,
+    num::{Num, Overflow},
+    read_file,
+    term::{Cell, Colour, Frame, FrameSink, Renderer},
+};
+use std::{collections::hash_set::HashSet, time::Duration};
+
+/// Set to true to watch the propagation of the beams on the terminal.
+const VISUALIZE: bool = false;
+
+/// Set to a file name to save a heatmap of the beam coverage.
+const HEATMAP: Option<&str> = None;
 
 type Manifold = Vec<Vec<u8>>;
 
@@ -7,12 +19,24 @@ fn parse_row(row: &str) -> Vec<u8> {
     row.as_bytes().to_vec()
 }
 
-fn part1(manifold: &Manifold) -> u64 {
+/// Build a frame showing the manifold, where beams are marked with `|`.
+fn manifold_frame(manifold: &Manifold, caption: String) -> Frame {
+    Frame::from_grid(caption, manifold, |_i, _j, &x| match x {
+        b'S' => Cell::new('S', Colour::Yellow),
+        b'^' => Cell::new('^', Colour::Magenta),
+        b'|' => Cell::new('|', Colour::Cyan),
+        _ => Cell::new('.', Colour::Default),
+    })
+}
+
+fn part1(manifold: &Manifold, mut frames: Option<&mut dyn FrameSink>) -> u64 {
     let start = manifold[0].iter().position(|&x| x == b'S').unwrap();
     let mut beams = HashSet::from([start]);
     let mut newbeams = HashSet::new();
     let mut splits = 0;
-    for row in manifold.iter().skip(1) {
+    // copy of the manifold where beams are drawn, only used for visualization
+    let mut drawing = manifold.clone();
+    for (i, row) in manifold.iter().enumerate().skip(1) {
         for &beam in &beams {
             if row[beam] == b'^' {
                 splits += 1;
@@ -24,43 +48,117 @@ fn part1(manifold: &Manifold) -> u64 {
         }
         beams.drain();
         std::mem::swap(&mut beams, &mut newbeams);
+        if let Some(sink) = frames.as_deref_mut() {
+            for &beam in &beams {
+                drawing[i][beam] = b'|';
+            }
+            sink.publish(manifold_frame(
+                &drawing,
+                format!("row {i}: {splits} splits"),
+            ));
+        }
     }
     splits
 }
 
-fn part2_inner(manifold: &Manifold, row: usize, col: usize, cache: &mut Vec<Vec<u64>>) -> u64 {
-    if cache[row][col] == 0 {
-        if row == manifold.len() - 1 {
-            cache[row][col] = 1;
-        } else if manifold[row][col] == b'^' {
-            let left = part2_inner(manifold, row + 1, col - 1, cache);
-            let right = part2_inner(manifold, row + 1, col + 1, cache);
-            cache[row][col] = left + right
-        } else {
-            cache[row][col] = part2_inner(manifold, row + 1, col, cache)
-        }
+fn part2_inner<N: Num>(
+    manifold: &Manifold,
+    row: usize,
+    col: usize,
+    memo: &mut Memo<(usize, usize), N>,
+) -> Result<N, Overflow> {
+    if let Some(result) = memo.get(&(row, col)) {
+        return Ok(result.clone());
     }
-    cache[row][col]
+    let result = if row == manifold.len() - 1 {
+        N::one()
+    } else if manifold[row][col] == b'^' {
+        let left = part2_inner(manifold, row + 1, col - 1, memo)?;
+        let right = part2_inner(manifold, row + 1, col + 1, memo)?;
+        left.add(&right)?
+    } else {
+        part2_inner(manifold, row + 1, col, memo)?
+    };
+    memo.insert((row, col), result.clone());
+    Ok(result)
 }
 
-fn part2(manifold: &Manifold) -> u64 {
-    let mut cache = vec![vec![0; manifold[0].len()]; manifold.len()];
+fn part2<N: Num>(manifold: &Manifold) -> Result<N, Overflow> {
     let start = manifold[0].iter().position(|&x| x == b'S').unwrap();
-    part2_inner(manifold, 0, start, &mut cache)
+    part2_inner(manifold, 0, start, &mut Memo::new())
+}
+
+/// Compute, for each cell of the manifold, the number of timelines whose beam passes through
+/// it. Counts are approximated by floating point numbers, since they are only used to draw
+/// a heatmap.
+fn coverage(manifold: &Manifold) -> Vec<Vec<f64>> {
+    let mut result = vec![vec![0.0; manifold[0].len()]; manifold.len()];
+    let start = manifold[0].iter().position(|&x| x == b'S').unwrap();
+    result[0][start] = 1.0;
+    for i in 1..manifold.len() {
+        for j in 0..manifold[i].len() {
+            let timelines = result[i - 1][j];
+            if timelines == 0.0 {
+                continue;
+            }
+            if manifold[i][j] == b'^' {
+                result[i][j - 1] += timelines;
+                result[i][j + 1] += timelines;
+            } else {
+                result[i][j] += timelines;
+            }
+        }
+    }
+    result
+}
+
+/// Save a heatmap of the beam coverage, using a logarithmic scale. Splitters are grey.
+fn export_heatmap(manifold: &Manifold, filename: &str) -> std::io::Result<()> {
+    let coverage = coverage(manifold);
+    let max = coverage.iter().flatten().fold(0.0f64, |a, &b| a.max(b));
+    let image = Image::from_grid(&coverage, 4, |i, j, &timelines| {
+        if manifold[i][j] == b'^' {
+            Rgb::GREY
+        } else {
+            heat(timelines.ln_1p() / max.ln_1p())
+        }
+    });
+    image.save(filename)
 }
 
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle7_example.txt", "\n", parse_row);
-    assert_eq!(part1(&content), 21);
-    assert_eq!(part2(&content), 40);
+    let mut frames = Vec::new();
+    assert_eq!(part1(&content, Some(&mut frames)), 21);
+    assert_eq!(frames.len(), content.len() - 1);
+    assert_eq!(frames.last().unwrap().to_plain()[2], "......|^|......");
+    assert_eq!(part2::<u64>(&content), Ok(40));
+    assert_eq!(coverage(&content).last().unwrap().iter().sum::<f64>(), 40.0);
     let content = read_file("inputs/puzzle7.txt", "\n", parse_row);
-    assert_eq!(part1(&content), 1533);
-    assert_eq!(part2(&content), 10733529153890);
+    assert_eq!(part1(&content, None), 1533);
+    assert_eq!(part2::<u64>(&content), Ok(10733529153890));
+    // a full pyramid of splitters doubles the number of timelines at each row
+    let levels = 70;
+    let mut content = vec![vec![b'.'; 2 * levels + 1]; levels + 2];
+    content[0][levels] = b'S';
+    for k in 0..levels {
+        for j in 0..=k {
+            content[k + 1][levels - k + 2 * j] = b'^';
+        }
+    }
+    assert_eq!(part2::<u64>(&content), Err(Overflow));
+    assert_eq!(part2::<u128>(&content), Ok(1 << levels));
 }
 
 fn main() {
     let content = read_file("inputs/puzzle7.txt", "\n", parse_row);
-    println!("Part 1: {}", part1(&content));
-    println!("Part 2: {}", part2(&content));
+    // the renderer locks the standard input, so it is only created when needed
+    let mut renderer = VISUALIZE.then(|| Renderer::stdout().with_delay(Duration::from_millis(50)));
+    let frames = renderer.as_mut().map(|r| r as &mut dyn FrameSink);
+    println!("Part 1: {}", part1(&content, frames));
+    println!("Part 2: {}", part2::<u64>(&content).unwrap());
+    if let Some(filename) = HEATMAP {
+        export_heatmap(&content, filename).unwrap();
+    }
 }
@@ -1,4 +1,11 @@
-use aoc2025::read_file;
+use aoc2025::{
+    num::{Num, Overflow, Signed},
+    read_file,
+    svg::{Style, Svg},
+};
+
+/// Set to a file name to save an SVG drawing of the junction boxes and of their connections.
+const SVG_FILE: Option<&str> = None;
 
 type JunctionBox = (i64, i64, i64);
 type Dist = i64;
@@ -78,7 +85,7 @@ fn dist(c1: JunctionBox, c2: JunctionBox) -> Dist {
 
 /// Returns the list of pairs of junction box indices, ordered according to the
 /// distance between the corresponding junction boxes.
-fn sorted_pairs(content: &Vec<JunctionBox>) -> Vec<(usize, usize)> {
+fn sorted_pairs(content: &[JunctionBox]) -> Vec<(usize, usize)> {
     let mut matrix = Vec::new();
     for i in 0..content.len() {
         for j in i + 1..content.len() {
@@ -89,8 +96,8 @@ fn sorted_pairs(content: &Vec<JunctionBox>) -> Vec<(usize, usize)> {
     matrix
 }
 
-fn part1(junction_boxes: &Vec<JunctionBox>, joins: usize) -> usize {
-    let sorted_pairs = sorted_pairs(&junction_boxes);
+fn part1(junction_boxes: &[JunctionBox], joins: usize) -> usize {
+    let sorted_pairs = sorted_pairs(junction_boxes);
     let mut g = UnionFind::new(junction_boxes.len());
     for &(j1, j2) in sorted_pairs.iter().take(joins) {
         g.union(j1, j2);
@@ -103,30 +110,72 @@ fn part1(junction_boxes: &Vec<JunctionBox>, joins: usize) -> usize {
         .product()
 }
 
-fn part2(junction_boxes: &Vec<JunctionBox>) -> i64 {
-    let sorted_pairs = sorted_pairs(&junction_boxes);
+/// Returns the pairs of junction boxes which are connected, in order of distance, until all
+/// the junction boxes are in the same group. Pairs of junction boxes which are already in the
+/// same group are skipped, hence the result is a minimum spanning tree.
+fn connecting_pairs(junction_boxes: &[JunctionBox]) -> Vec<(usize, usize)> {
     let mut g = UnionFind::new(junction_boxes.len());
-    for (p1, p2) in sorted_pairs {
-        g.union(p1, p2);
+    let mut result = Vec::new();
+    for (p1, p2) in sorted_pairs(junction_boxes) {
         if g.groups == 1 {
-            return junction_boxes[p1].0 * junction_boxes[p2].0;
+            break;
+        }
+        if g.find(p1) != g.find(p2) {
+            g.union(p1, p2);
+            result.push((p1, p2));
         }
     }
-    panic!("This shouldn't happen!");
+    result
+}
+
+/// The X coordinates may be negative, hence the product carries a sign.
+fn part2<N: Num>(junction_boxes: &[JunctionBox]) -> Result<Signed<N>, Overflow> {
+    let &(p1, p2) = connecting_pairs(junction_boxes)
+        .last()
+        .expect("This shouldn't happen!");
+    let x1 = Signed::from_i64(junction_boxes[p1].0);
+    x1.mul(&Signed::from_i64(junction_boxes[p2].0))
+}
+
+/// Projects a junction box on the plane using an isometric projection, where the y axis
+/// points upward.
+fn project((x, y, z): JunctionBox) -> (f64, f64) {
+    let angle = 30f64.to_radians();
+    let (x, y, z) = (x as f64, y as f64, z as f64);
+    ((x - z) * angle.cos(), (x + z) * angle.sin() - y)
+}
+
+/// Saves an SVG drawing of the junction boxes, together with the connections of part 2.
+fn export_svg(junction_boxes: &[JunctionBox], filename: &str) -> std::io::Result<()> {
+    let mut svg = Svg::new(1000.0).with_background("white");
+    for (p1, p2) in connecting_pairs(junction_boxes) {
+        let (b1, b2) = (junction_boxes[p1], junction_boxes[p2]);
+        svg.line(project(b1), project(b2), Style::stroke("#1f77b4", 1.0));
+    }
+    for &b in junction_boxes {
+        svg.circle(project(b), 2.0, Style::fill("#d62728"));
+    }
+    svg.save(filename)
 }
 
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle8_example.txt", "\n", parse_junction_box);
     assert_eq!(part1(&content, 10), 40);
-    assert_eq!(part2(&content), 25272);
+    assert_eq!(part2::<u64>(&content), Ok(Signed::from_i64(25272)));
+    assert_eq!(connecting_pairs(&content).len(), content.len() - 1);
     let content = read_file("inputs/puzzle8.txt", "\n", parse_junction_box);
     assert_eq!(part1(&content, 1000), 84968);
-    assert_eq!(part2(&content), 8663467782);
+    assert_eq!(part2::<u64>(&content), Ok(Signed::from_i64(8663467782)));
+    let content = vec![(2, 0, 0), (-50, 0, 0), (3, 0, 0)];
+    assert_eq!(part2::<u64>(&content), Ok(Signed::from_i64(-100)));
 }
 
 fn main() {
     let content = read_file("inputs/puzzle8.txt", "\n", parse_junction_box);
     println!("Part 1: {}", part1(&content, 1000));
-    println!("Part 2: {}", part2(&content));
+    println!("Part 2: {}", part2::<u64>(&content).unwrap());
+    if let Some(filename) = SVG_FILE {
+        export_svg(&content, filename).unwrap();
+    }
 }
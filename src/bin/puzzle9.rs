@@ -1,8 +1,18 @@
-use aoc2025::read_file;
+use aoc2025::{
+    image::{Image, Rgb},
+    num::{Num, Overflow},
+    read_file,
+    svg::{Style, Svg},
+};
+
+/// Set to a file name to save an image of the interior of the polygon.
+const EXPORT_FILE: Option<&str> = None;
+
+/// Set to a file name to save an SVG drawing of the polygon and of the rectangle of part 2.
+const SVG_FILE: Option<&str> = None;
 
 type Point = (i64, i64);
 type Segment = (Point, Point);
-type Area = i64;
 
 /// Parse a row of the file and return a point.
 fn parse_point(row: &str) -> Point {
@@ -11,15 +21,17 @@ fn parse_point(row: &str) -> Point {
 }
 
 /// Compute the area of a rectangle, given its opposite corners.
-fn area_rectangle((x1, y1): Point, (x2, y2): Point) -> Area {
-    ((x1 - x2).abs() + 1) * ((y1 - y2).abs() + 1)
+fn area_rectangle<N: Num>((x1, y1): Point, (x2, y2): Point) -> Result<N, Overflow> {
+    let width = x1.abs_diff(x2).checked_add(1).ok_or(Overflow)?;
+    let height = y1.abs_diff(y2).checked_add(1).ok_or(Overflow)?;
+    N::from_u64(width).mul(&N::from_u64(height))
 }
 
 /// Compute the vector of segments which make the border of the polygon.
 /// The extremal points of each segment are ordered according to the lexicographic
 /// ordering that, given that all segments are either horizontal or vertical,
 /// correspond to the coordinate-wise ordering.
-fn corners_to_segments(corners: &Vec<Point>) -> Vec<Segment> {
+fn corners_to_segments(corners: &[Point]) -> Vec<Segment> {
     let mut result = Vec::new();
     let mut pred: (i64, i64) = corners[corners.len() - 1];
     for &curr in corners {
@@ -31,7 +43,7 @@ fn corners_to_segments(corners: &Vec<Point>) -> Vec<Segment> {
 }
 
 /// Determine if a point is on the border of the polygon.
-fn on_border(segments: &Vec<Segment>, (x, y): Point) -> bool {
+fn on_border(segments: &[Segment], (x, y): Point) -> bool {
     segments
         .iter()
         .any(|&((x1, y1), (x2, y2))| x1 <= x && x <= x2 && y1 <= y && y <= y2)
@@ -42,7 +54,7 @@ fn on_border(segments: &Vec<Segment>, (x, y): Point) -> bool {
 /// since this is the correct way to determin whether a point is inside the polygon.
 ///
 /// It might be useful to memoize the results of this function.
-fn count_x_crosses(segments: &Vec<Segment>, (x, y): Point) -> u64 {
+fn count_x_crosses(segments: &[Segment], (x, y): Point) -> u64 {
     let mut cross_x = 0;
     for &((x1, y1), (x2, y2)) in segments {
         // note that in y1 <= y < y2 the second inequality is strict
@@ -59,7 +71,7 @@ fn count_x_crosses(segments: &Vec<Segment>, (x, y): Point) -> u64 {
 /// are not considered part of the segments.
 ///
 /// It might be useful to memoize the results of this function.
-fn count_crosses(segments: &Vec<Segment>, (x, y): Point) -> (u64, u64) {
+fn count_crosses(segments: &[Segment], (x, y): Point) -> (u64, u64) {
     let mut cross_x = 0;
     let mut cross_y = 0;
     for &((x1, y1), (x2, y2)) in segments {
@@ -74,14 +86,14 @@ fn count_crosses(segments: &Vec<Segment>, (x, y): Point) -> (u64, u64) {
 }
 
 /// Determine whether `p` is inside the polygon or on the border of the polygon.
-fn is_inside(segments: &Vec<Segment>, p: Point) -> bool {
+fn is_inside(segments: &[Segment], p: Point) -> bool {
     count_x_crosses(segments, p) % 2 == 1 || on_border(segments, p)
 }
 
 /// Determine if the square with opposite corners in `p1` and `p2` is inside
 /// the polygon. This only works if there are no adjacent parallel segments
 /// in the border of the polygon. This seems to be the case in the input data.
-fn is_safe_rectangle(segments: &Vec<Segment>, (x1, y1): Point, (x2, y2): Point) -> bool {
+fn is_safe_rectangle(segments: &[Segment], (x1, y1): Point, (x2, y2): Point) -> bool {
     // determine corners of the rectangle
     let ul = (x1.min(x2), y1.min(y2));
     let ur = (x1.max(x2), y1.min(y2));
@@ -107,44 +119,127 @@ fn is_safe_rectangle(segments: &Vec<Segment>, (x1, y1): Point, (x2, y2): Point)
     no_crosses
 }
 
-/// Compute the rectangle with largest area present in the polygon
-/// `corners`, subject to the safety condition `safety_check`.
-fn largest_rectangle<T>(corners: &Vec<Point>, safety_check: T) -> Area
+/// Compute the rectangle with largest area present in the polygon `corners`, subject
+/// to the safety condition `safety_check`. Return its opposite corners and its area, or
+/// `None` if no pair of corners satisfies the condition.
+fn best_rectangle<N, T>(
+    corners: &[Point],
+    safety_check: T,
+) -> Result<Option<(Point, Point, N)>, Overflow>
 where
+    N: Num,
     T: Fn(Point, Point) -> bool,
 {
-    let mut maxval = 0;
+    let mut best: Option<(Point, Point, N)> = None;
     for i in 0..corners.len() {
-        for j in i+1..corners.len() {
+        for j in i + 1..corners.len() {
             if safety_check(corners[i], corners[j]) {
-                maxval = maxval.max(area_rectangle(corners[i], corners[j]))
+                let area = area_rectangle(corners[i], corners[j])?;
+                if best.as_ref().is_none_or(|(_, _, maxval)| area > *maxval) {
+                    best = Some((corners[i], corners[j], area))
+                }
             }
         }
     }
-    maxval
+    Ok(best)
+}
+
+/// Compute the area of the rectangle with largest area present in the polygon
+/// `corners`, subject to the safety condition `safety_check`.
+fn largest_rectangle<N, T>(corners: &[Point], safety_check: T) -> Result<N, Overflow>
+where
+    N: Num,
+    T: Fn(Point, Point) -> bool,
+{
+    let best = best_rectangle(corners, safety_check)?;
+    Ok(best.map_or(N::zero(), |(_, _, area)| area))
 }
 
-fn part1(corners: &Vec<Point>) -> Area {
+fn part1<N: Num>(corners: &[Point]) -> Result<N, Overflow> {
     largest_rectangle(corners, |_p1, _p2| true)
 }
 
-fn part2(corners: &Vec<Point>) -> Area {
+fn part2<N: Num>(corners: &[Point]) -> Result<N, Overflow> {
     let segments = corners_to_segments(corners);
     largest_rectangle(corners, |p1, p2| is_safe_rectangle(&segments, p1, p2))
 }
 
+/// Save an image of the polygon `corners`, where the longest side of its bounding box is
+/// `size` pixels long. Each pixel is green if the corresponding point is inside the polygon.
+fn export_interior(corners: &[Point], size: usize, filename: &str) -> std::io::Result<()> {
+    let segments = corners_to_segments(corners);
+    let minx = corners.iter().map(|p| p.0).min().unwrap();
+    let maxx = corners.iter().map(|p| p.0).max().unwrap();
+    let miny = corners.iter().map(|p| p.1).min().unwrap();
+    let maxy = corners.iter().map(|p| p.1).max().unwrap();
+    // number of units for each pixel
+    let scale = ((maxx - minx).max(maxy - miny) as f64 + 1.0) / size as f64;
+    let width = ((maxx - minx + 1) as f64 / scale).ceil() as usize;
+    let height = ((maxy - miny + 1) as f64 / scale).ceil() as usize;
+    let image = Image::from_fn(width, height, |x, y| {
+        let p = (
+            minx + (x as f64 * scale) as i64,
+            miny + (y as f64 * scale) as i64,
+        );
+        if is_inside(&segments, p) {
+            Rgb::GREEN
+        } else {
+            Rgb::WHITE
+        }
+    });
+    image.save(filename)
+}
+
+/// Save an SVG drawing of the polygon `corners`, highlighting the largest safe rectangle.
+fn export_svg(corners: &[Point], filename: &str) -> std::io::Result<()> {
+    let to_f64 = |(x, y): Point| (x as f64, y as f64);
+    let segments = corners_to_segments(corners);
+    let mut svg = Svg::new(1000.0).with_background("white");
+    svg.polygon(
+        corners.iter().map(|&p| to_f64(p)).collect(),
+        Style::fill("#c7e9c0"),
+    );
+    for &(p1, p2) in &segments {
+        svg.line(to_f64(p1), to_f64(p2), Style::stroke("#2ca02c", 1.0));
+    }
+    let best = best_rectangle::<u128, _>(corners, |p1, p2| is_safe_rectangle(&segments, p1, p2))
+        .map_err(std::io::Error::other)?;
+    if let Some((p1, p2, _area)) = best {
+        let style = Style::stroke("#d62728", 2.0)
+            .with_fill("#d62728")
+            .with_opacity(0.4);
+        svg.rect(to_f64(p1), to_f64(p2), style);
+    }
+    svg.save(filename)
+}
+
 #[test]
 fn test() {
     let content = read_file("inputs/puzzle9_example.txt", "\n", parse_point);
-    assert_eq!(part1(&content), 50);
-    assert_eq!(part2(&content), 24);
+    assert_eq!(part1::<u64>(&content), Ok(50));
+    assert_eq!(part2::<u64>(&content), Ok(24));
+    let segments = corners_to_segments(&content);
+    let best = best_rectangle(&content, |p1, p2| is_safe_rectangle(&segments, p1, p2));
+    assert_eq!(best, Ok(Some(((9, 5), (2, 3), 24u64))));
     let content = read_file("inputs/puzzle9.txt", "\n", parse_point);
-    assert_eq!(part1(&content), 4782896435);
-    assert_eq!(part2(&content), 1540060480);
+    assert_eq!(part1::<u64>(&content), Ok(4782896435));
+    assert_eq!(part2::<u64>(&content), Ok(1540060480));
+    let content = vec![(0, 0), (1 << 40, 1 << 40)];
+    assert_eq!(part1::<u64>(&content), Err(Overflow));
+    assert_eq!(
+        part1::<u128>(&content),
+        Ok(((1 << 40) + 1) * ((1 << 40) + 1))
+    );
 }
 
 fn main() {
     let content = read_file("inputs/puzzle9.txt", "\n", parse_point);
-    println!("Part 1: {}", part1(&content));
-    println!("Part 2: {}", part2(&content));
+    println!("Part 1: {}", part1::<u64>(&content).unwrap());
+    println!("Part 2: {}", part2::<u64>(&content).unwrap());
+    if let Some(filename) = EXPORT_FILE {
+        export_interior(&content, 800, filename).unwrap();
+    }
+    if let Some(filename) = SVG_FILE {
+        export_svg(&content, filename).unwrap();
+    }
 }
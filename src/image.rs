@@ -0,0 +1,260 @@
+//! Export of grids and geometry as image files.
+//!
+//! Images are kept in memory as a matrix of RGB pixels, and may be saved as binary PPM,
+//! as binary PGM (converting colours to grey levels) or as PNG. None of these formats
+//! requires external dependencies: PNG files are written without compression, which makes
+//! them large but universally readable.
+
+use std::{
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+};
+
+/// A colour, with its red, green and blue components.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct Rgb(pub u8, pub u8, pub u8);
+
+impl Rgb {
+    pub const BLACK: Rgb = Rgb(0, 0, 0);
+    pub const WHITE: Rgb = Rgb(255, 255, 255);
+    pub const RED: Rgb = Rgb(220, 40, 40);
+    pub const GREEN: Rgb = Rgb(40, 160, 60);
+    pub const BLUE: Rgb = Rgb(40, 80, 200);
+    pub const GREY: Rgb = Rgb(128, 128, 128);
+
+    /// Return the grey level of the colour.
+    pub fn luma(self) -> u8 {
+        ((self.0 as u32 * 299 + self.1 as u32 * 587 + self.2 as u32 * 114) / 1000) as u8
+    }
+}
+
+/// Return a colour on a heat scale from black (`t = 0`) through red and yellow to white
+/// (`t = 1`). Values of `t` outside the unit interval are clamped.
+pub fn heat(t: f64) -> Rgb {
+    let t = t.clamp(0.0, 1.0) * 3.0;
+    let channel = |x: f64| (x.clamp(0.0, 1.0) * 255.0).round() as u8;
+    Rgb(channel(t), channel(t - 1.0), channel(t - 2.0))
+}
+
+/// Return the `i`-th colour of a palette of easily distinguishable colours. The palette is
+/// cyclic, so any index is valid.
+pub fn palette(i: usize) -> Rgb {
+    const COLOURS: [Rgb; 10] = [
+        Rgb(31, 119, 180),
+        Rgb(255, 127, 14),
+        Rgb(44, 160, 44),
+        Rgb(214, 39, 40),
+        Rgb(148, 103, 189),
+        Rgb(140, 86, 75),
+        Rgb(227, 119, 194),
+        Rgb(127, 127, 127),
+        Rgb(188, 189, 34),
+        Rgb(23, 190, 207),
+    ];
+    COLOURS[i % COLOURS.len()]
+}
+
+/// An image in memory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Image {
+    width: usize,
+    height: usize,
+    /// The pixels, row by row.
+    pixels: Vec<Rgb>,
+}
+
+impl Image {
+    /// Create an image filled with the `background` colour.
+    pub fn new(width: usize, height: usize, background: Rgb) -> Self {
+        Self {
+            width,
+            height,
+            pixels: vec![background; width * height],
+        }
+    }
+
+    /// Create an image where the colour of the pixel in column `x` and row `y` is
+    /// given by `colour(x, y)`.
+    pub fn from_fn<F>(width: usize, height: usize, colour: F) -> Self
+    where
+        F: Fn(usize, usize) -> Rgb,
+    {
+        let pixels = (0..height)
+            .flat_map(|y| (0..width).map(move |x| (x, y)))
+            .map(|(x, y)| colour(x, y))
+            .collect();
+        Self {
+            width,
+            height,
+            pixels,
+        }
+    }
+
+    /// Create an image from a grid, where each cell becomes a square of `scale` x `scale`
+    /// pixels whose colour is given by `colour(i, j, cell)`, for the cell in row `i` and
+    /// column `j`. Rows shorter than the longest one are padded with black pixels.
+    pub fn from_grid<T, F>(grid: &[Vec<T>], scale: usize, colour: F) -> Self
+    where
+        F: Fn(usize, usize, &T) -> Rgb,
+    {
+        let width = grid.iter().map(|row| row.len()).max().unwrap_or(0);
+        let mut image = Self::new(width * scale, grid.len() * scale, Rgb::BLACK);
+        for (i, row) in grid.iter().enumerate() {
+            for (j, cell) in row.iter().enumerate() {
+                let c = colour(i, j, cell);
+                for y in i * scale..(i + 1) * scale {
+                    image.pixels[y * image.width + j * scale..y * image.width + (j + 1) * scale]
+                        .fill(c);
+                }
+            }
+        }
+        image
+    }
+
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Return the colour of the pixel in column `x` and row `y`.
+    pub fn get(&self, x: usize, y: usize) -> Rgb {
+        self.pixels[y * self.width + x]
+    }
+
+    /// Set the colour of the pixel in column `x` and row `y`.
+    pub fn set(&mut self, x: usize, y: usize, colour: Rgb) {
+        self.pixels[y * self.width + x] = colour
+    }
+
+    /// Write the image in the binary PPM format.
+    pub fn write_ppm<W: Write>(&self, mut out: W) -> io::Result<()> {
+        write!(out, "P6\n{} {}\n255\n", self.width, self.height)?;
+        let data: Vec<u8> = self.pixels.iter().flat_map(|p| [p.0, p.1, p.2]).collect();
+        out.write_all(&data)
+    }
+
+    /// Write the image in the binary PGM format, converting colours to grey levels.
+    pub fn write_pgm<W: Write>(&self, mut out: W) -> io::Result<()> {
+        write!(out, "P5\n{} {}\n255\n", self.width, self.height)?;
+        let data: Vec<u8> = self.pixels.iter().map(|p| p.luma()).collect();
+        out.write_all(&data)
+    }
+
+    /// Write the image in the PNG format, without compression.
+    pub fn write_png<W: Write>(&self, mut out: W) -> io::Result<()> {
+        out.write_all(b"\x89PNG\r\n\x1a\n")?;
+
+        let mut header = Vec::new();
+        header.extend((self.width as u32).to_be_bytes());
+        header.extend((self.height as u32).to_be_bytes());
+        // bit depth 8, truecolour, default compression, filtering and no interlacing
+        header.extend([8, 2, 0, 0, 0]);
+        write_png_chunk(&mut out, b"IHDR", &header)?;
+
+        // each scanline starts with the filter type, which is always 0 (no filtering)
+        let mut raw = Vec::with_capacity((3 * self.width + 1) * self.height);
+        for row in self.pixels.chunks(self.width.max(1)).take(self.height) {
+            raw.push(0);
+            raw.extend(row.iter().flat_map(|p| [p.0, p.1, p.2]));
+        }
+        write_png_chunk(&mut out, b"IDAT", &zlib_stored(&raw))?;
+
+        write_png_chunk(&mut out, b"IEND", &[])
+    }
+
+    /// Save the image to `path`, choosing the format from the extension of the file, which
+    /// should be one of `ppm`, `pgm` or `png`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        let path = path.as_ref();
+        let extension = path.extension().and_then(|e| e.to_str()).unwrap_or("");
+        let write = match extension {
+            "ppm" => Image::write_ppm,
+            "pgm" => Image::write_pgm,
+            "png" => Image::write_png,
+            _ => {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    format!("unknown image format: {}", path.display()),
+                ));
+            }
+        };
+        write(self, BufWriter::new(File::create(path)?))
+    }
+}
+
+/// Write a PNG chunk with the given type and data, followed by its checksum.
+fn write_png_chunk<W: Write>(out: &mut W, kind: &[u8; 4], data: &[u8]) -> io::Result<()> {
+    out.write_all(&(data.len() as u32).to_be_bytes())?;
+    out.write_all(kind)?;
+    out.write_all(data)?;
+    let crc = crc32(kind.iter().chain(data));
+    out.write_all(&crc.to_be_bytes())
+}
+
+/// Compute the CRC-32 checksum used by PNG.
+fn crc32<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut crc = 0xffffffffu32;
+    for &byte in bytes {
+        crc ^= byte as u32;
+        for _ in 0..8 {
+            crc = if crc & 1 == 1 {
+                (crc >> 1) ^ 0xedb88320
+            } else {
+                crc >> 1
+            };
+        }
+    }
+    !crc
+}
+
+/// Wrap `data` in a zlib stream made of uncompressed (stored) deflate blocks.
+fn zlib_stored(data: &[u8]) -> Vec<u8> {
+    let mut result = vec![0x78, 0x01];
+    let mut blocks = data.chunks(0xffff).peekable();
+    if blocks.peek().is_none() {
+        // an empty stream still needs a final block
+        result.extend([1, 0, 0, 0xff, 0xff]);
+    }
+    while let Some(block) = blocks.next() {
+        result.push(blocks.peek().is_none() as u8);
+        result.extend((block.len() as u16).to_le_bytes());
+        result.extend((!(block.len() as u16)).to_le_bytes());
+        result.extend(block);
+    }
+    // Adler-32 checksum of the uncompressed data
+    let (mut a, mut b) = (1u32, 0u32);
+    for &byte in data {
+        a = (a + byte as u32) % 65521;
+        b = (b + a) % 65521;
+    }
+    result.extend(((b << 16) | a).to_be_bytes());
+    result
+}
+
+#[test]
+fn test() {
+    let grid = vec![vec![0, 1], vec![2, 3]];
+    let image = Image::from_grid(&grid, 2, |_i, _j, &x| Rgb(x * 80, 0, 0));
+    assert_eq!((image.width(), image.height()), (4, 4));
+    assert_eq!(image.get(3, 1), Rgb(80, 0, 0));
+    assert_eq!(image.get(1, 2), Rgb(160, 0, 0));
+
+    let mut ppm = Vec::new();
+    image.write_ppm(&mut ppm).unwrap();
+    assert!(ppm.starts_with(b"P6\n4 4\n255\n"));
+    assert_eq!(ppm.len(), 11 + 4 * 4 * 3);
+
+    let mut pgm = Vec::new();
+    Image::from_fn(2, 1, |x, _y| if x == 0 { Rgb::WHITE } else { Rgb::BLACK })
+        .write_pgm(&mut pgm)
+        .unwrap();
+    assert_eq!(pgm, b"P5\n2 1\n255\n\xff\x00");
+
+    assert_eq!(crc32(b"IEND".iter()), 0xae426082);
+    assert_eq!(heat(0.0), Rgb::BLACK);
+    assert_eq!(heat(1.0), Rgb::WHITE);
+}
@@ -0,0 +1,250 @@
+//! Closed intervals of integers.
+//!
+//! Intervals are pairs `(start, end)` with both extremes included, as they appear in the
+//! puzzle inputs. An interval with `start > end` is empty.
+
+use std::fmt::Debug;
+
+/// An integer type whose values have a successor, except for the largest one, and a
+/// predecessor, except for the smallest one.
+pub trait Discrete: Copy + Ord + Debug {
+    /// Return the value following `self`, or `None` if `self` is the largest value.
+    fn succ(self) -> Option<Self>;
+
+    /// Return the value preceding `self`, or `None` if `self` is the smallest value.
+    fn pred(self) -> Option<Self>;
+
+    /// Return the position of `self` among the values of the type, starting from 0 for the
+    /// smallest one, so that the number of values from `a` to `b` is `b.index() - a.index() + 1`.
+    fn index(self) -> u128;
+}
+
+macro_rules! impl_discrete {
+    ($($t:ty),* ; $index:expr) => {
+        $(
+            impl Discrete for $t {
+                fn succ(self) -> Option<Self> {
+                    self.checked_add(1)
+                }
+
+                fn pred(self) -> Option<Self> {
+                    self.checked_sub(1)
+                }
+
+                #[allow(clippy::unnecessary_cast)]
+                fn index(self) -> u128 {
+                    $index(self)
+                }
+            }
+        )*
+    };
+}
+
+impl_discrete!(u8, u16, u32, u64, u128, usize; |x| x as u128);
+// flipping the sign bit maps the negative numbers below the positive ones
+impl_discrete!(i8, i16, i32, i64, i128, isize; |x| (x as i128 as u128) ^ (1 << 127));
+
+/// Sort the intervals and merge those which overlap or are adjacent, discarding the empty
+/// ones. The result is a list of disjoint, non-adjacent intervals in increasing order.
+pub fn normalize<T: Discrete>(intervals: &[(T, T)]) -> Vec<(T, T)> {
+    let mut sorted: Vec<(T, T)> = intervals.iter().copied().filter(|(l, r)| l <= r).collect();
+    sorted.sort();
+    let mut result: Vec<(T, T)> = Vec::with_capacity(sorted.len());
+    for (l, r) in sorted {
+        match result.last_mut() {
+            // merge when `l` does not come after the value following the last interval
+            Some(last) if last.1.succ().is_none_or(|next| l <= next) => {
+                last.1 = last.1.max(r);
+            }
+            _ => result.push((l, r)),
+        }
+    }
+    result
+}
+
+/// A set of integers, stored as a list of disjoint, non-adjacent intervals in increasing order.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct IntervalSet<T> {
+    intervals: Vec<(T, T)>,
+}
+
+impl<T: Discrete> IntervalSet<T> {
+    /// Return the set of the integers in any of the intervals.
+    pub fn new(intervals: &[(T, T)]) -> Self {
+        Self {
+            intervals: normalize(intervals),
+        }
+    }
+
+    pub fn empty() -> Self {
+        Self {
+            intervals: Vec::new(),
+        }
+    }
+
+    /// Return the intervals of the set, disjoint, non-adjacent and in increasing order.
+    pub fn intervals(&self) -> &[(T, T)] {
+        &self.intervals
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.intervals.is_empty()
+    }
+
+    /// Determine whether `x` belongs to the set, with a binary search.
+    pub fn contains(&self, x: T) -> bool {
+        let i = self.intervals.partition_point(|&(_, r)| r < x);
+        self.intervals.get(i).is_some_and(|&(l, _)| l <= x)
+    }
+
+    /// Determine whether any integer from `l` to `r`, both included, belongs to the set.
+    pub fn intersects(&self, (l, r): (T, T)) -> bool {
+        let i = self.intervals.partition_point(|&(_, hi)| hi < l);
+        l <= r && self.intervals.get(i).is_some_and(|&(lo, _)| lo <= r)
+    }
+
+    /// Return the number of integers in the set.
+    ///
+    /// # Panics
+    ///
+    /// If the set holds all the 2^128 values of a 128-bit type.
+    pub fn cardinality(&self) -> u128 {
+        self.intervals
+            .iter()
+            .map(|&(l, r)| (r.index() - l.index()).checked_add(1))
+            .try_fold(0u128, |acc, n| acc.checked_add(n?))
+            .expect("the set has 2^128 values")
+    }
+
+    pub fn union(&self, other: &Self) -> Self {
+        let mut intervals = self.intervals.clone();
+        intervals.extend_from_slice(&other.intervals);
+        Self::new(&intervals)
+    }
+
+    /// Return the intersection of the sets, walking both lists of intervals in order.
+    pub fn intersection(&self, other: &Self) -> Self {
+        let mut intervals = Vec::new();
+        let (mut a, mut b) = (
+            self.intervals.iter().peekable(),
+            other.intervals.iter().peekable(),
+        );
+        while let (Some(&&(l1, r1)), Some(&&(l2, r2))) = (a.peek(), b.peek()) {
+            let (l, r) = (l1.max(l2), r1.min(r2));
+            if l <= r {
+                intervals.push((l, r));
+            }
+            // the interval ending first cannot meet any of the following ones
+            if r1 < r2 {
+                a.next();
+            } else {
+                b.next();
+            }
+        }
+        Self { intervals }
+    }
+
+    /// Return the integers from `bounds.0` to `bounds.1`, both included, which are not in the
+    /// set.
+    pub fn complement(&self, bounds: (T, T)) -> Self {
+        let mut intervals = Vec::new();
+        // the first integer which may be in the complement, if any
+        let mut start = Some(bounds.0);
+        for &(l, r) in &self.intervals {
+            let Some(s) = start.filter(|&s| s <= bounds.1) else {
+                break;
+            };
+            if r < s {
+                continue;
+            }
+            if l > s {
+                intervals.push((s, l.pred().unwrap().min(bounds.1)));
+            }
+            start = r.succ();
+        }
+        if let Some(s) = start.filter(|&s| s <= bounds.1) {
+            intervals.push((s, bounds.1));
+        }
+        Self { intervals }
+    }
+
+    /// Return the integers in `self` which are not in `other`.
+    pub fn difference(&self, other: &Self) -> Self {
+        match (self.intervals.first(), self.intervals.last()) {
+            (Some(&(l, _)), Some(&(_, r))) => self.intersection(&other.complement((l, r))),
+            _ => Self::empty(),
+        }
+    }
+}
+
+impl<T: Discrete> FromIterator<(T, T)> for IntervalSet<T> {
+    fn from_iter<I: IntoIterator<Item = (T, T)>>(iter: I) -> Self {
+        Self::new(&iter.into_iter().collect::<Vec<_>>())
+    }
+}
+
+#[test]
+fn test() {
+    assert_eq!(
+        normalize(&[(10, 14), (3, 5), (16, 20), (12, 18), (6, 7), (9, 8)]),
+        vec![(3, 7), (10, 20)]
+    );
+    assert_eq!(normalize::<u64>(&[]), vec![]);
+    assert_eq!(normalize(&[(0u8, 255), (3, 4), (255, 255)]), vec![(0, 255)]);
+    assert_eq!(normalize(&[(-5i32, -1), (1, 2)]), vec![(-5, -1), (1, 2)]);
+}
+
+#[test]
+fn test_interval_set() {
+    // compare with the sets of small integers
+    let to_set = |s: &IntervalSet<i8>| -> Vec<i8> {
+        (i8::MIN..=i8::MAX).filter(|&x| s.contains(x)).collect()
+    };
+    let mut rng = crate::rng::Lcg::new(3);
+    let mut random = |n: u64| rng.below(n);
+    for _ in 0..300 {
+        let mut sets = Vec::new();
+        for _ in 0..2 {
+            let intervals: Vec<(i8, i8)> = (0..random(5))
+                .map(|_| {
+                    let l = random(256) as u8 as i8;
+                    (l, l.saturating_add(random(40) as i8))
+                })
+                .collect();
+            let set = IntervalSet::new(&intervals);
+            let expected: Vec<i8> = (i8::MIN..=i8::MAX)
+                .filter(|&x| intervals.iter().any(|&(l, r)| l <= x && x <= r))
+                .collect();
+            assert_eq!(to_set(&set), expected);
+            assert_eq!(set.cardinality(), expected.len() as u128);
+            sets.push((set, expected));
+        }
+        let ((a, xs), (b, ys)) = (&sets[0], &sets[1]);
+        let union: Vec<i8> = (i8::MIN..=i8::MAX)
+            .filter(|x| xs.contains(x) || ys.contains(x))
+            .collect();
+        assert_eq!(to_set(&a.union(b)), union);
+        let intersection: Vec<i8> = xs.iter().copied().filter(|x| ys.contains(x)).collect();
+        assert_eq!(to_set(&a.intersection(b)), intersection);
+        let difference: Vec<i8> = xs.iter().copied().filter(|x| !ys.contains(x)).collect();
+        assert_eq!(to_set(&a.difference(b)), difference);
+        for &(l, r) in b.intervals() {
+            assert_eq!(a.intersects((l, r)), (l..=r).any(|x| xs.contains(&x)));
+        }
+        assert!(!a.intersects((5, -5)));
+        for bounds in [(i8::MIN, i8::MAX), (-20, 30), (0, 0), (5, -5)] {
+            let complement = a.complement(bounds);
+            let expected: Vec<i8> = (bounds.0..=bounds.1).filter(|x| !xs.contains(x)).collect();
+            assert_eq!(to_set(&complement), expected);
+            assert_eq!(complement, IntervalSet::new(complement.intervals()));
+        }
+    }
+    let all = IntervalSet::new(&[(0u64, u64::MAX)]);
+    assert_eq!(all.cardinality(), 1 << 64);
+    assert!(all.complement((0, u64::MAX)).is_empty());
+    assert_eq!(
+        IntervalSet::new(&[(3u64, 5)]).complement((0, u64::MAX)),
+        IntervalSet::new(&[(0, 2), (6, u64::MAX)])
+    );
+    assert_eq!(IntervalSet::new(&[(i128::MIN, -1)]).cardinality(), 1 << 127);
+}
@@ -1,3 +1,12 @@
+pub mod image;
+pub mod interval;
+pub mod memo;
+pub mod num;
+pub mod rng;
+pub mod selection;
+pub mod svg;
+pub mod term;
+
 /// Read the file `filename`, splitting its content on the pattern `pat`. Each of the pieces
 /// is passed to the `parser` function, and it returns a vector of the elements returned by
 /// `parser`.
@@ -0,0 +1,164 @@
+//! Memoization helper shared by the recursive solutions.
+//!
+//! A `Memo` is a hash table from arguments to results which makes the "not computed yet"
+//! state explicit (lookups return an `Option`), can use any hashing strategy, may be bounded
+//! in size and keeps track of hits and misses.
+
+use std::{
+    borrow::Borrow,
+    collections::{HashMap, hash_map::RandomState},
+    hash::{BuildHasher, BuildHasherDefault, Hash, Hasher},
+};
+
+/// Statistics on the usage of a memo table.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct MemoStats {
+    /// Number of lookups which found a value.
+    pub hits: u64,
+    /// Number of lookups which did not find a value.
+    pub misses: u64,
+    /// Number of values which were not stored because the table was full.
+    pub dropped: u64,
+}
+
+/// A memo table from keys of type `K` to values of type `V`, using the hashing strategy `S`.
+#[derive(Debug, Clone)]
+pub struct Memo<K, V, S = RandomState> {
+    table: HashMap<K, V, S>,
+    limit: Option<usize>,
+    stats: MemoStats,
+}
+
+impl<K: Hash + Eq, V> Memo<K, V> {
+    /// Create an empty, unbounded memo table with the default hashing strategy.
+    pub fn new() -> Self {
+        Self::with_hasher(RandomState::new())
+    }
+}
+
+impl<K: Hash + Eq, V> Default for Memo<K, V> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<K: Hash + Eq, V, S: BuildHasher> Memo<K, V, S> {
+    /// Create an empty, unbounded memo table which hashes keys with `hasher`.
+    pub fn with_hasher(hasher: S) -> Self {
+        Self {
+            table: HashMap::with_hasher(hasher),
+            limit: None,
+            stats: MemoStats::default(),
+        }
+    }
+
+    /// Bound the table to at most `limit` entries. Once the table is full, new values are
+    /// not stored anymore: results are still correct, but they might be computed again.
+    pub fn bounded(mut self, limit: usize) -> Self {
+        self.limit = Some(limit);
+        self
+    }
+
+    /// Return the value memoized for `key`, or `None` if it has not been computed yet.
+    pub fn get<Q>(&mut self, key: &Q) -> Option<&V>
+    where
+        K: Borrow<Q>,
+        Q: Hash + Eq + ?Sized,
+    {
+        let result = self.table.get(key);
+        if result.is_some() {
+            self.stats.hits += 1;
+        } else {
+            self.stats.misses += 1;
+        }
+        result
+    }
+
+    /// Memoize `value` as the result for `key`. Return `false` if the value has not been
+    /// stored because the table is full.
+    pub fn insert(&mut self, key: K, value: V) -> bool {
+        if self.limit.is_some_and(|limit| self.table.len() >= limit)
+            && !self.table.contains_key(&key)
+        {
+            self.stats.dropped += 1;
+            return false;
+        }
+        self.table.insert(key, value);
+        true
+    }
+
+    /// Return the number of memoized values.
+    pub fn len(&self) -> usize {
+        self.table.len()
+    }
+
+    /// Determine whether no value has been memoized.
+    pub fn is_empty(&self) -> bool {
+        self.table.is_empty()
+    }
+
+    /// Return the usage statistics of the table.
+    pub fn stats(&self) -> MemoStats {
+        self.stats
+    }
+}
+
+/// The FNV-1a hash function. It is much faster than the default hasher on small keys, at the
+/// cost of not being resistant to collision attacks, which is irrelevant here.
+#[derive(Debug, Clone, Copy)]
+pub struct FnvHasher(u64);
+
+impl Default for FnvHasher {
+    fn default() -> Self {
+        Self(0xcbf29ce484222325)
+    }
+}
+
+impl Hasher for FnvHasher {
+    fn finish(&self) -> u64 {
+        self.0
+    }
+
+    fn write(&mut self, bytes: &[u8]) {
+        for &byte in bytes {
+            self.0 ^= byte as u64;
+            self.0 = self.0.wrapping_mul(0x100000001b3);
+        }
+    }
+}
+
+/// Hashing strategy based on `FnvHasher`, to be used with `Memo::with_hasher`.
+pub type FnvBuildHasher = BuildHasherDefault<FnvHasher>;
+
+#[test]
+fn test() {
+    let mut memo = Memo::new();
+    assert_eq!(memo.get(&1), None);
+    memo.insert(1, 0u64);
+    assert_eq!(memo.get(&1), Some(&0));
+    assert_eq!(
+        memo.stats(),
+        MemoStats {
+            hits: 1,
+            misses: 1,
+            dropped: 0
+        }
+    );
+
+    let mut memo = Memo::with_hasher(FnvBuildHasher::default()).bounded(2);
+    assert!(memo.insert("a".to_string(), 1));
+    assert!(memo.insert("b".to_string(), 2));
+    assert!(!memo.insert("c".to_string(), 3));
+    assert!(memo.insert("a".to_string(), 4));
+    assert_eq!(memo.get("a"), Some(&4));
+    assert_eq!(memo.get("c"), None);
+    assert_eq!(memo.len(), 2);
+    assert_eq!(
+        memo.stats(),
+        MemoStats {
+            hits: 1,
+            misses: 1,
+            dropped: 1
+        }
+    );
+}
@@ -0,0 +1,319 @@
+//! Numeric abstraction used by the solutions whose answers might not fit a machine word.
+//!
+//! The `Num` trait hides the backend used for the arithmetic, which is selected through
+//! the type parameter of the solver:
+//!   * `u64` and `u128` check every operation and report an `Overflow` error;
+//!   * `Wrapping<u64>` silently wraps around, reproducing the behaviour of release builds;
+//!   * `BigUint` never overflows.
+//!
+//! `Signed` adds a sign to any of the backends, for the answers which may be negative.
+
+use std::{cmp::Ordering, fmt, num::Wrapping};
+
+/// Error returned when the result of an operation does not fit the numeric backend.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Overflow;
+
+impl fmt::Display for Overflow {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "arithmetic overflow")
+    }
+}
+
+impl std::error::Error for Overflow {}
+
+/// A non-negative number supporting the operations needed by the solutions.
+pub trait Num: Clone + PartialEq + PartialOrd + fmt::Debug + fmt::Display {
+    /// The additive unit.
+    fn zero() -> Self;
+
+    /// The multiplicative unit.
+    fn one() -> Self;
+
+    /// Convert a machine integer into the backend.
+    fn from_u64(n: u64) -> Self;
+
+    /// Compute `self + other`.
+    fn add(&self, other: &Self) -> Result<Self, Overflow>;
+
+    /// Compute `self * other`.
+    fn mul(&self, other: &Self) -> Result<Self, Overflow>;
+}
+
+impl Num for u64 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn from_u64(n: u64) -> Self {
+        n
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Overflow> {
+        self.checked_add(*other).ok_or(Overflow)
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Overflow> {
+        self.checked_mul(*other).ok_or(Overflow)
+    }
+}
+
+impl Num for u128 {
+    fn zero() -> Self {
+        0
+    }
+
+    fn one() -> Self {
+        1
+    }
+
+    fn from_u64(n: u64) -> Self {
+        n as u128
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Overflow> {
+        self.checked_add(*other).ok_or(Overflow)
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Overflow> {
+        self.checked_mul(*other).ok_or(Overflow)
+    }
+}
+
+impl Num for Wrapping<u64> {
+    fn zero() -> Self {
+        Wrapping(0)
+    }
+
+    fn one() -> Self {
+        Wrapping(1)
+    }
+
+    fn from_u64(n: u64) -> Self {
+        Wrapping(n)
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Overflow> {
+        Ok(self + other)
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Overflow> {
+        Ok(self * other)
+    }
+}
+
+/// Sum all the elements of `iter`, stopping at the first overflow.
+pub fn sum<N: Num>(iter: impl IntoIterator<Item = N>) -> Result<N, Overflow> {
+    iter.into_iter().try_fold(N::zero(), |acc, x| acc.add(&x))
+}
+
+/// Multiply all the elements of `iter`, stopping at the first overflow.
+pub fn product<N: Num>(iter: impl IntoIterator<Item = N>) -> Result<N, Overflow> {
+    iter.into_iter().try_fold(N::one(), |acc, x| acc.mul(&x))
+}
+
+/// Parse a string of decimal digits, stopping at the first overflow. Panics if `digits`
+/// contains anything else than the digits `0` to `9`.
+pub fn from_decimal<N: Num>(digits: &str) -> Result<N, Overflow> {
+    let ten = N::from_u64(10);
+    digits.bytes().try_fold(N::zero(), |acc, c| {
+        assert!(c.is_ascii_digit(), "invalid decimal digit {:?}", c as char);
+        acc.mul(&ten)?.add(&N::from_u64((c - b'0') as u64))
+    })
+}
+
+/// A number with a sign, whose magnitude is stored in the backend `N`. Zero is never negative.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signed<N> {
+    pub negative: bool,
+    pub magnitude: N,
+}
+
+impl<N: Num> Signed<N> {
+    pub fn from_i64(n: i64) -> Self {
+        Self {
+            negative: n < 0,
+            magnitude: N::from_u64(n.unsigned_abs()),
+        }
+    }
+
+    /// Compute `self * other`.
+    pub fn mul(&self, other: &Self) -> Result<Self, Overflow> {
+        let magnitude = self.magnitude.mul(&other.magnitude)?;
+        Ok(Self {
+            negative: self.negative != other.negative && magnitude != N::zero(),
+            magnitude,
+        })
+    }
+}
+
+impl<N: fmt::Display> fmt::Display for Signed<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.negative {
+            write!(f, "-")?;
+        }
+        write!(f, "{}", self.magnitude)
+    }
+}
+
+/// An arbitrary precision unsigned integer.
+///
+/// The number is stored as a vector of base 2^32 digits, starting from the least significant
+/// one. The vector never has trailing zeros, hence zero is represented by the empty vector.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct BigUint(Vec<u32>);
+
+impl BigUint {
+    /// Remove the most significant digits which are zero.
+    fn normalize(mut self) -> Self {
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        self
+    }
+
+    /// Divide the number by the small value `d` in place, returning the remainder.
+    fn div_rem_small(&mut self, d: u32) -> u32 {
+        let mut rem = 0u64;
+        for digit in self.0.iter_mut().rev() {
+            let cur = (rem << 32) | *digit as u64;
+            *digit = (cur / d as u64) as u32;
+            rem = cur % d as u64;
+        }
+        while self.0.last() == Some(&0) {
+            self.0.pop();
+        }
+        rem as u32
+    }
+}
+
+impl From<u64> for BigUint {
+    fn from(n: u64) -> Self {
+        BigUint(vec![n as u32, (n >> 32) as u32]).normalize()
+    }
+}
+
+impl Ord for BigUint {
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.0
+            .len()
+            .cmp(&other.0.len())
+            .then_with(|| self.0.iter().rev().cmp(other.0.iter().rev()))
+    }
+}
+
+impl PartialOrd for BigUint {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl fmt::Display for BigUint {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.0.is_empty() {
+            return write!(f, "0");
+        }
+        // extract groups of nine decimal digits, starting from the least significant one
+        let mut n = self.clone();
+        let mut groups = Vec::new();
+        while !n.0.is_empty() {
+            groups.push(n.div_rem_small(1_000_000_000));
+        }
+        write!(f, "{}", groups.pop().unwrap())?;
+        for group in groups.iter().rev() {
+            write!(f, "{group:09}")?;
+        }
+        Ok(())
+    }
+}
+
+impl Num for BigUint {
+    fn zero() -> Self {
+        BigUint(Vec::new())
+    }
+
+    fn one() -> Self {
+        BigUint(vec![1])
+    }
+
+    fn from_u64(n: u64) -> Self {
+        n.into()
+    }
+
+    fn add(&self, other: &Self) -> Result<Self, Overflow> {
+        let len = self.0.len().max(other.0.len());
+        let mut result = Vec::with_capacity(len + 1);
+        let mut carry = 0u64;
+        for i in 0..len {
+            let a = *self.0.get(i).unwrap_or(&0) as u64;
+            let b = *other.0.get(i).unwrap_or(&0) as u64;
+            let cur = a + b + carry;
+            result.push(cur as u32);
+            carry = cur >> 32;
+        }
+        result.push(carry as u32);
+        Ok(BigUint(result).normalize())
+    }
+
+    fn mul(&self, other: &Self) -> Result<Self, Overflow> {
+        let mut result = vec![0u32; self.0.len() + other.0.len()];
+        for (i, &a) in self.0.iter().enumerate() {
+            let mut carry = 0u64;
+            for (j, &b) in other.0.iter().enumerate() {
+                let cur = result[i + j] as u64 + a as u64 * b as u64 + carry;
+                result[i + j] = cur as u32;
+                carry = cur >> 32;
+            }
+            result[i + other.0.len()] = carry as u32;
+        }
+        Ok(BigUint(result).normalize())
+    }
+}
+
+#[test]
+fn test() {
+    assert_eq!(u64::MAX.add(&1), Err(Overflow));
+    assert_eq!((u64::MAX as u128).add(&1), Ok(1 << 64));
+    assert_eq!(Wrapping(u64::MAX).add(&Wrapping(2)), Ok(Wrapping(1)));
+    assert_eq!(product([1u64 << 32, 1 << 32]), Err(Overflow));
+    assert_eq!(product([1u128 << 32, 1 << 32]), Ok(1 << 64));
+
+    let big = product((1..=30).map(BigUint::from_u64)).unwrap();
+    assert_eq!(big.to_string(), "265252859812191058636308480000000");
+    assert_eq!(
+        sum([big.clone(), BigUint::one()]).unwrap().to_string(),
+        "265252859812191058636308480000001"
+    );
+    assert!(big > BigUint::from_u64(u64::MAX));
+    assert_eq!(from_decimal::<u64>("18446744073709551615"), Ok(u64::MAX));
+    assert_eq!(from_decimal::<u64>("18446744073709551616"), Err(Overflow));
+    assert_eq!(
+        from_decimal::<BigUint>("265252859812191058636308480000000"),
+        Ok(big.clone())
+    );
+    assert_eq!(from_decimal::<u128>(""), Ok(0));
+    assert_eq!(BigUint::zero().to_string(), "0");
+    assert_eq!(
+        BigUint::from_u64(u64::MAX).to_string(),
+        u64::MAX.to_string()
+    );
+
+    let min = Signed::<u128>::from_i64(i64::MIN);
+    assert_eq!(
+        min.mul(&Signed::from_i64(-2)).unwrap().to_string(),
+        "18446744073709551616"
+    );
+    assert_eq!(
+        min.mul(&Signed::from_i64(3)).unwrap().to_string(),
+        "-27670116110564327424"
+    );
+    assert_eq!(min.mul(&Signed::from_i64(0)), Ok(Signed::from_i64(0)));
+    assert_eq!(
+        Signed::<u64>::from_i64(i64::MIN).mul(&Signed::from_i64(-2)),
+        Err(Overflow)
+    );
+}
@@ -0,0 +1,44 @@
+//! Deterministic pseudo-random numbers for the randomized tests.
+//!
+//! This is Knuth's MMIX linear congruential generator: it is not suitable for anything but
+//! generating reproducible test inputs. It is not restricted to `cfg(test)` because the
+//! tests of the binaries link against the non-test build of the library.
+
+/// A linear congruential generator.
+#[derive(Debug, Clone)]
+pub struct Lcg(u64);
+
+impl Lcg {
+    /// Create a generator starting from `seed`.
+    pub fn new(seed: u64) -> Self {
+        Self(seed)
+    }
+
+    /// Advance the generator and return its whole state. The low bits are of poor quality.
+    pub fn next_u64(&mut self) -> u64 {
+        self.0 = self
+            .0
+            .wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407);
+        self.0
+    }
+
+    /// Return a number in `0..n`, taken from the high bits of the state.
+    pub fn below(&mut self, n: u64) -> u64 {
+        (self.next_u64() >> 33) % n
+    }
+}
+
+#[test]
+fn test_lcg() {
+    let mut a = Lcg::new(5);
+    let mut b = Lcg::new(5);
+    let xs: Vec<u64> = (0..100).map(|_| a.below(7)).collect();
+    assert!(xs.iter().all(|&x| x < 7));
+    assert!((0..7).all(|k| xs.contains(&k)));
+    assert_eq!(
+        b.next_u64(),
+        5u64.wrapping_mul(6364136223846793005)
+            .wrapping_add(1442695040888963407)
+    );
+}
@@ -0,0 +1,231 @@
+//! Selections of digits from a sequence, such as the banks of batteries of puzzle 3.
+//!
+//! A selection of `k` digits keeps their order in the sequence, so it is described by the
+//! increasing list of the chosen indices, and its value is the number written with the chosen
+//! digits. Since all the selections of a query have the same length, comparing their values
+//! is the same as comparing their digits lexicographically. When several selections have the
+//! same value, the functions return the one with the leftmost indices.
+
+/// Return the value of the selection `indices` of `digits`, as a string of decimal digits.
+pub fn to_string(digits: &[u8], indices: &[usize]) -> String {
+    indices
+        .iter()
+        .map(|&i| (b'0' + digits[i]) as char)
+        .collect()
+}
+
+/// Return the selection of `k` digits with the largest value, in a single pass. The indices of
+/// the chosen digits are kept on a stack: a new digit removes from the stack the smaller digits
+/// which precede it, as long as enough digits are left to complete the selection.
+pub fn max_indices(digits: &[u8], k: usize) -> Vec<usize> {
+    assert!(
+        k <= digits.len(),
+        "cannot select {k} of {} digits",
+        digits.len()
+    );
+    let mut stack: Vec<usize> = Vec::with_capacity(k);
+    for (i, &d) in digits.iter().enumerate() {
+        // the digits from `i` onwards, plus those on the stack, must be at least `k`
+        while let Some(&top) = stack.last() {
+            if digits[top] < d && stack.len() + digits.len() - i > k {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+        if stack.len() < k {
+            stack.push(i);
+        }
+    }
+    stack
+}
+
+/// Return the selection of `k` digits with the smallest value, among those whose first digit
+/// is not zero, or `None` if there is no such selection. The first digit is the leftmost
+/// occurrence of the smallest non-zero digit which leaves enough digits after it, and the other
+/// ones are chosen as in `max_indices`, with the comparison reversed.
+pub fn min_indices(digits: &[u8], k: usize) -> Option<Vec<usize>> {
+    if k == 0 || k > digits.len() {
+        return (k == 0).then(Vec::new);
+    }
+    let first = (0..=digits.len() - k)
+        .filter(|&i| digits[i] != 0)
+        .min_by_key(|&i| digits[i])?;
+    let mut stack = vec![first];
+    for (i, &d) in digits.iter().enumerate().skip(first + 1) {
+        while let Some(&top) = stack.last() {
+            if top != first && digits[top] > d && stack.len() + digits.len() - i > k {
+                stack.pop();
+            } else {
+                break;
+            }
+        }
+        if stack.len() < k {
+            stack.push(i);
+        }
+    }
+    Some(stack)
+}
+
+/// Return the selection of `k` digits with the `rank`-th largest value, counting each value
+/// once, or `None` if there are fewer than `rank` distinct values. The largest value has rank
+/// `1`.
+///
+/// The selections are ranked digit by digit: the distinct values with `l` digits taken from
+/// the digits from `i` onwards and starting with `d` are `d` followed by the distinct values
+/// with `l - 1` digits taken after the first occurrence of `d`. Counting them takes time and
+/// memory proportional to the length of `digits` times `k`.
+pub fn kth_max_indices(digits: &[u8], k: usize, rank: u64) -> Option<Vec<usize>> {
+    assert!(rank >= 1, "ranks start from 1");
+    let n = digits.len();
+    // next[i][d] is the first occurrence of `d` from position `i` onwards, or `n`
+    let mut next = vec![[n; 10]; n + 1];
+    for i in (0..n).rev() {
+        next[i] = next[i + 1];
+        next[i][digits[i] as usize] = i;
+    }
+    // count[i][l] is the number of distinct values with `l` digits taken from position `i`
+    // onwards, saturating since we only compare it with `rank`
+    let mut count = vec![vec![0u64; k + 1]; n + 2];
+    for i in (0..=n + 1).rev() {
+        count[i][0] = 1;
+        if i >= n {
+            continue;
+        }
+        for l in 1..=k {
+            count[i][l] = next[i]
+                .iter()
+                .filter(|&&j| j < n)
+                .fold(0u64, |acc, &j| acc.saturating_add(count[j + 1][l - 1]));
+        }
+    }
+    if count[0][k] < rank {
+        return None;
+    }
+    let mut rank = rank;
+    let mut indices = Vec::with_capacity(k);
+    let mut i = 0;
+    for l in (1..=k).rev() {
+        for d in (0..10).rev() {
+            let j = next[i][d];
+            if j == n {
+                continue;
+            }
+            if rank <= count[j + 1][l - 1] {
+                indices.push(j);
+                i = j + 1;
+                break;
+            }
+            rank -= count[j + 1][l - 1];
+        }
+    }
+    Some(indices)
+}
+
+/// Return the selection of `k` digits with the largest value which does not use any of the
+/// `forbidden` positions, or `None` if there are not enough allowed positions.
+pub fn max_indices_avoiding(digits: &[u8], k: usize, forbidden: &[usize]) -> Option<Vec<usize>> {
+    let mut allowed = vec![true; digits.len()];
+    for &i in forbidden {
+        if i < digits.len() {
+            allowed[i] = false;
+        }
+    }
+    let positions: Vec<usize> = (0..digits.len()).filter(|&i| allowed[i]).collect();
+    if positions.len() < k {
+        return None;
+    }
+    let kept: Vec<u8> = positions.iter().map(|&i| digits[i]).collect();
+    Some(
+        max_indices(&kept, k)
+            .into_iter()
+            .map(|j| positions[j])
+            .collect(),
+    )
+}
+
+/// Return the selection of `k` digits with the largest value such that consecutive chosen
+/// indices differ by at least `gap`, or `None` if no such selection exists.
+///
+/// If the `j`-th digit is at position `i`, the remaining `k - j - 1` digits fit exactly when
+/// `i + (k - j - 1) * gap` is a valid position. Choosing the leftmost largest digit in the
+/// window of valid positions never excludes a better selection, since it leaves the most room
+/// for the following digits. This takes time proportional to the length of `digits` times `k`.
+pub fn max_indices_spaced(digits: &[u8], k: usize, gap: usize) -> Option<Vec<usize>> {
+    let gap = gap.max(1);
+    if k == 0 {
+        return Some(Vec::new());
+    }
+    if (k - 1) * gap >= digits.len() {
+        return None;
+    }
+    let mut indices = Vec::with_capacity(k);
+    let mut start = 0;
+    for j in 0..k {
+        let end = digits.len() - 1 - (k - j - 1) * gap;
+        let best = (start..=end).rev().max_by_key(|&i| digits[i]).unwrap();
+        indices.push(best);
+        start = best + gap;
+    }
+    Some(indices)
+}
+
+#[test]
+fn test() {
+    // compare with all the selections of `k` out of `n` digits
+    let all_selections = |n: usize, k: usize| -> Vec<Vec<usize>> {
+        (0u32..1 << n)
+            .filter(|mask| mask.count_ones() as usize == k)
+            .map(|mask| (0..n).filter(|&i| mask >> i & 1 == 1).collect())
+            .collect()
+    };
+    let mut rng = crate::rng::Lcg::new(7);
+    for _ in 0..200 {
+        let seed = rng.next_u64();
+        let n = (seed >> 60) as usize % 9 + 1;
+        let digits: Vec<u8> = (0..n)
+            .map(|i| ((seed >> (4 * i)) % 4) as u8 * 3 % 10)
+            .collect();
+        for k in 0..=n {
+            let mut selections = all_selections(n, k);
+            // sort by decreasing value, then by increasing indices
+            selections.sort_by(|a, b| {
+                to_string(&digits, b)
+                    .cmp(&to_string(&digits, a))
+                    .then_with(|| a.cmp(b))
+            });
+            assert_eq!(max_indices(&digits, k), selections[0]);
+
+            let min = selections
+                .iter()
+                .filter(|s| s.first().is_none_or(|&i| digits[i] != 0))
+                .min_by(|a, b| to_string(&digits, a).cmp(&to_string(&digits, b)));
+            assert_eq!(min_indices(&digits, k), min.cloned(), "{digits:?} {k}");
+
+            let mut values: Vec<String> =
+                selections.iter().map(|s| to_string(&digits, s)).collect();
+            values.dedup();
+            for rank in 1..=values.len() as u64 + 1 {
+                let result = kth_max_indices(&digits, k, rank);
+                let expected = values.get(rank as usize - 1);
+                assert_eq!(result.map(|s| to_string(&digits, &s)).as_ref(), expected);
+            }
+
+            let forbidden = [1, 4, 20];
+            let allowed = selections
+                .iter()
+                .find(|s| s.iter().all(|i| !forbidden.contains(i)));
+            assert_eq!(
+                max_indices_avoiding(&digits, k, &forbidden).as_ref(),
+                allowed
+            );
+
+            for gap in 1..4 {
+                let spaced = selections
+                    .iter()
+                    .find(|s| s.windows(2).all(|w| w[1] - w[0] >= gap));
+                assert_eq!(max_indices_spaced(&digits, k, gap).as_ref(), spaced);
+            }
+        }
+    }
+}
@@ -0,0 +1,279 @@
+//! Export of geometry as SVG files.
+//!
+//! Shapes are added in data coordinates, which may be very large (the puzzle inputs use
+//! coordinates around 10^5). When the drawing is rendered, coordinates are normalized so
+//! that the bounding box of all the shapes fits the requested width, keeping the aspect ratio.
+//! As in SVG, the y axis points downward. Stroke widths and radii of circles are given in
+//! pixels of the final drawing, hence they are not affected by the normalization.
+
+use std::{fmt::Write as _, fs, io, path::Path};
+
+/// The style used to draw a shape. Colours are given in any syntax understood by SVG, such
+/// as `red` or `#1f77b4`.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Style {
+    pub stroke: Option<String>,
+    pub stroke_width: f64,
+    pub fill: Option<String>,
+    pub opacity: f64,
+}
+
+impl Default for Style {
+    fn default() -> Self {
+        Self {
+            stroke: Some("black".to_string()),
+            stroke_width: 1.0,
+            fill: None,
+            opacity: 1.0,
+        }
+    }
+}
+
+impl Style {
+    /// A style which only draws the outline of shapes, with the given colour and width.
+    pub fn stroke(colour: &str, width: f64) -> Self {
+        Self {
+            stroke: Some(colour.to_string()),
+            stroke_width: width,
+            ..Self::default()
+        }
+    }
+
+    /// A style which only fills shapes with the given colour.
+    pub fn fill(colour: &str) -> Self {
+        Self {
+            stroke: None,
+            fill: Some(colour.to_string()),
+            ..Self::default()
+        }
+    }
+
+    /// Return the same style, filling shapes with the given colour.
+    pub fn with_fill(self, colour: &str) -> Self {
+        Self {
+            fill: Some(colour.to_string()),
+            ..self
+        }
+    }
+
+    /// Return the same style with the given opacity, between 0 and 1.
+    pub fn with_opacity(self, opacity: f64) -> Self {
+        Self { opacity, ..self }
+    }
+
+    /// Return the SVG attributes corresponding to the style.
+    fn attributes(&self) -> String {
+        format!(
+            r#"stroke="{}" stroke-width="{}" fill="{}" opacity="{}""#,
+            self.stroke.as_deref().unwrap_or("none"),
+            self.stroke_width,
+            self.fill.as_deref().unwrap_or("none"),
+            self.opacity
+        )
+    }
+}
+
+/// A point in data coordinates.
+pub type Point = (f64, f64);
+
+/// The shapes which may be drawn.
+#[derive(Debug, Clone, PartialEq)]
+enum Shape {
+    Polygon(Vec<Point>),
+    Polyline(Vec<Point>),
+    Line(Point, Point),
+    /// A rectangle, given by two opposite corners.
+    Rect(Point, Point),
+    /// A circle, given by its center and its radius in pixels.
+    Circle(Point, f64),
+}
+
+/// An SVG drawing.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Svg {
+    /// Width of the drawing, in pixels.
+    width: f64,
+    /// Empty space around the shapes, in pixels.
+    margin: f64,
+    /// Optional background colour.
+    background: Option<String>,
+    shapes: Vec<(Shape, Style)>,
+}
+
+impl Svg {
+    /// Create an empty drawing which is `width` pixels wide. The height is computed from the
+    /// aspect ratio of the bounding box of the shapes.
+    pub fn new(width: f64) -> Self {
+        Self {
+            width,
+            margin: 10.0,
+            background: None,
+            shapes: Vec::new(),
+        }
+    }
+
+    /// Set the empty space around the shapes, in pixels.
+    pub fn with_margin(mut self, margin: f64) -> Self {
+        self.margin = margin;
+        self
+    }
+
+    /// Set the background colour of the drawing.
+    pub fn with_background(mut self, colour: &str) -> Self {
+        self.background = Some(colour.to_string());
+        self
+    }
+
+    /// Add a closed polygon with the given vertices.
+    pub fn polygon(&mut self, points: Vec<Point>, style: Style) -> &mut Self {
+        self.shapes.push((Shape::Polygon(points), style));
+        self
+    }
+
+    /// Add an open line through the given points.
+    pub fn polyline(&mut self, points: Vec<Point>, style: Style) -> &mut Self {
+        self.shapes.push((Shape::Polyline(points), style));
+        self
+    }
+
+    /// Add a segment from `p1` to `p2`.
+    pub fn line(&mut self, p1: Point, p2: Point, style: Style) -> &mut Self {
+        self.shapes.push((Shape::Line(p1, p2), style));
+        self
+    }
+
+    /// Add an axis-aligned rectangle with opposite corners `p1` and `p2`.
+    pub fn rect(&mut self, p1: Point, p2: Point, style: Style) -> &mut Self {
+        self.shapes.push((Shape::Rect(p1, p2), style));
+        self
+    }
+
+    /// Add a circle centered in `center`, whose radius is `radius` pixels.
+    pub fn circle(&mut self, center: Point, radius: f64, style: Style) -> &mut Self {
+        self.shapes.push((Shape::Circle(center, radius), style));
+        self
+    }
+
+    /// Return the bounding box of all the shapes, as the pair of its top-left and
+    /// bottom-right corners.
+    fn bounds(&self) -> (Point, Point) {
+        let points = self.shapes.iter().flat_map(|(shape, _)| match shape {
+            Shape::Polygon(points) | Shape::Polyline(points) => points.clone(),
+            Shape::Line(p1, p2) | Shape::Rect(p1, p2) => vec![*p1, *p2],
+            Shape::Circle(center, _) => vec![*center],
+        });
+        points.fold(
+            (
+                (f64::INFINITY, f64::INFINITY),
+                (f64::NEG_INFINITY, f64::NEG_INFINITY),
+            ),
+            |((minx, miny), (maxx, maxy)), (x, y)| {
+                ((minx.min(x), miny.min(y)), (maxx.max(x), maxy.max(y)))
+            },
+        )
+    }
+
+    /// Render the drawing as the content of an SVG file.
+    pub fn render(&self) -> String {
+        let ((minx, miny), (maxx, maxy)) = if self.shapes.is_empty() {
+            ((0.0, 0.0), (0.0, 0.0))
+        } else {
+            self.bounds()
+        };
+        let extent = (maxx - minx).max(maxy - miny);
+        let scale = if extent > 0.0 {
+            (self.width - 2.0 * self.margin) / extent
+        } else {
+            1.0
+        };
+        let height = (maxy - miny) * scale + 2.0 * self.margin;
+        let tx = |x: f64| self.margin + (x - minx) * scale;
+        let ty = |y: f64| self.margin + (y - miny) * scale;
+        let points = |points: &[Point]| {
+            points
+                .iter()
+                .map(|&(x, y)| format!("{:.2},{:.2}", tx(x), ty(y)))
+                .collect::<Vec<_>>()
+                .join(" ")
+        };
+
+        let mut out = String::new();
+        writeln!(
+            out,
+            r#"<svg xmlns="http://www.w3.org/2000/svg" width="{w:.0}" height="{height:.0}" viewBox="0 0 {w:.2} {height:.2}">"#,
+            w = self.width
+        )
+        .unwrap();
+        if let Some(background) = &self.background {
+            writeln!(
+                out,
+                r#"<rect width="100%" height="100%" fill="{background}"/>"#
+            )
+            .unwrap();
+        }
+        for (shape, style) in &self.shapes {
+            let attributes = style.attributes();
+            match shape {
+                Shape::Polygon(ps) => {
+                    writeln!(out, r#"<polygon points="{}" {attributes}/>"#, points(ps))
+                }
+                Shape::Polyline(ps) => {
+                    writeln!(out, r#"<polyline points="{}" {attributes}/>"#, points(ps))
+                }
+                Shape::Line((x1, y1), (x2, y2)) => writeln!(
+                    out,
+                    r#"<line x1="{:.2}" y1="{:.2}" x2="{:.2}" y2="{:.2}" {attributes}/>"#,
+                    tx(*x1),
+                    ty(*y1),
+                    tx(*x2),
+                    ty(*y2)
+                ),
+                Shape::Rect((x1, y1), (x2, y2)) => writeln!(
+                    out,
+                    r#"<rect x="{:.2}" y="{:.2}" width="{:.2}" height="{:.2}" {attributes}/>"#,
+                    tx(x1.min(*x2)),
+                    ty(y1.min(*y2)),
+                    (x1 - x2).abs() * scale,
+                    (y1 - y2).abs() * scale
+                ),
+                Shape::Circle((x, y), r) => writeln!(
+                    out,
+                    r#"<circle cx="{:.2}" cy="{:.2}" r="{r}" {attributes}/>"#,
+                    tx(*x),
+                    ty(*y)
+                ),
+            }
+            .unwrap();
+        }
+        out.push_str("</svg>\n");
+        out
+    }
+
+    /// Save the drawing to the file `path`.
+    pub fn save<P: AsRef<Path>>(&self, path: P) -> io::Result<()> {
+        fs::write(path, self.render())
+    }
+}
+
+#[test]
+fn test() {
+    let mut svg = Svg::new(120.0);
+    svg.polygon(
+        vec![
+            (100000.0, 50000.0),
+            (200000.0, 50000.0),
+            (200000.0, 100000.0),
+        ],
+        Style::default().with_fill("green"),
+    )
+    .circle(
+        (100000.0, 50000.0),
+        2.0,
+        Style::fill("red").with_opacity(0.5),
+    );
+    let out = svg.render();
+    assert!(out.contains(r#"height="70""#));
+    assert!(out.contains(r#"points="10.00,10.00 110.00,10.00 110.00,60.00""#));
+    assert!(out.contains(r#"<circle cx="10.00" cy="10.00" r="2" stroke="none" stroke-width="1" fill="red" opacity="0.5"/>"#));
+    assert!(out.ends_with("</svg>\n"));
+}
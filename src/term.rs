@@ -0,0 +1,288 @@
+//! ANSI terminal renderer for grid simulations.
+//!
+//! A simulation publishes `Frame`s to a `FrameSink`. The `Renderer` sink draws each frame on
+//! the terminal, either continuously with a fixed delay between frames, or one frame at a time
+//! waiting for a command from the user. Commands are read one line at a time, since reading
+//! single keys would require putting the terminal in raw mode:
+//!   * an empty line shows the next frame;
+//!   * `c` continues the playback without waiting;
+//!   * `+` and `-` make the playback faster or slower;
+//!   * `q` stops rendering, and all the following frames are discarded.
+
+use std::{
+    io::{self, BufRead, Stdout, Write},
+    thread,
+    time::Duration,
+};
+
+/// The eight standard ANSI colours, plus the default colour of the terminal.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum Colour {
+    #[default]
+    Default,
+    Black,
+    Red,
+    Green,
+    Yellow,
+    Blue,
+    Magenta,
+    Cyan,
+    White,
+}
+
+impl Colour {
+    /// Return the offset of the colour in the ANSI colour codes.
+    fn offset(self) -> u8 {
+        match self {
+            Colour::Black => 0,
+            Colour::Red => 1,
+            Colour::Green => 2,
+            Colour::Yellow => 3,
+            Colour::Blue => 4,
+            Colour::Magenta => 5,
+            Colour::Cyan => 6,
+            Colour::White => 7,
+            Colour::Default => 9,
+        }
+    }
+}
+
+/// A cell of a frame: a symbol with its foreground and background colours.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cell {
+    pub symbol: char,
+    pub fg: Colour,
+    pub bg: Colour,
+}
+
+impl Cell {
+    /// Create a cell showing `symbol` in colour `fg` over the default background.
+    pub fn new(symbol: char, fg: Colour) -> Self {
+        Self {
+            symbol,
+            fg,
+            bg: Colour::Default,
+        }
+    }
+
+    /// Return the same cell over the background colour `bg`.
+    pub fn on(self, bg: Colour) -> Self {
+        Self { bg, ..self }
+    }
+}
+
+/// A snapshot of a grid simulation, with a caption describing it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Frame {
+    pub caption: String,
+    pub cells: Vec<Vec<Cell>>,
+}
+
+impl Frame {
+    /// Build a frame from a grid, using `style` to determine the aspect of the cell in row `i`
+    /// and column `j`.
+    pub fn from_grid<T, F>(caption: impl Into<String>, grid: &[Vec<T>], style: F) -> Self
+    where
+        F: Fn(usize, usize, &T) -> Cell,
+    {
+        let cells = grid
+            .iter()
+            .enumerate()
+            .map(|(i, row)| {
+                row.iter()
+                    .enumerate()
+                    .map(|(j, x)| style(i, j, x))
+                    .collect()
+            })
+            .collect();
+        Self {
+            caption: caption.into(),
+            cells,
+        }
+    }
+
+    /// Return the symbols of the frame, one string for each row, without colours.
+    pub fn to_plain(&self) -> Vec<String> {
+        self.cells
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.symbol).collect())
+            .collect()
+    }
+}
+
+/// Anything which receives the frames published by a simulation.
+pub trait FrameSink {
+    fn publish(&mut self, frame: Frame);
+}
+
+/// A vector simply records all the frames, so that they may be inspected or replayed later.
+impl FrameSink for Vec<Frame> {
+    fn publish(&mut self, frame: Frame) {
+        self.push(frame)
+    }
+}
+
+/// Draws frames on a terminal, reading playback commands from an input stream.
+pub struct Renderer<W, R> {
+    out: W,
+    input: R,
+    /// Delay between frames when the playback is not paused.
+    delay: Duration,
+    /// Whether we should wait for a command after each frame.
+    paused: bool,
+    /// Whether the user asked to stop rendering.
+    stopped: bool,
+    /// Number of frames rendered so far.
+    count: usize,
+}
+
+impl Renderer<Stdout, io::StdinLock<'static>> {
+    /// Create a renderer which draws on the standard output and reads commands from the
+    /// standard input.
+    pub fn stdout() -> Self {
+        Self::new(io::stdout(), io::stdin().lock())
+    }
+}
+
+impl<W: Write, R: BufRead> Renderer<W, R> {
+    /// Create a renderer which draws on `out` and reads commands from `input`. The playback
+    /// is initially running, with a delay of 100 ms between frames.
+    pub fn new(out: W, input: R) -> Self {
+        Self {
+            out,
+            input,
+            delay: Duration::from_millis(100),
+            paused: false,
+            stopped: false,
+            count: 0,
+        }
+    }
+
+    /// Set the delay between frames when the playback is running.
+    pub fn with_delay(mut self, delay: Duration) -> Self {
+        self.delay = delay;
+        self
+    }
+
+    /// Start the playback paused, waiting for a command after each frame.
+    pub fn stepping(mut self) -> Self {
+        self.paused = true;
+        self
+    }
+
+    /// Pause the playback, so that the renderer waits for a command after the next frame.
+    pub fn pause(&mut self) {
+        self.paused = true
+    }
+
+    /// Determine whether the user asked to stop rendering.
+    pub fn is_stopped(&self) -> bool {
+        self.stopped
+    }
+
+    /// Draw a frame and wait according to the current playback mode.
+    pub fn render(&mut self, frame: &Frame) -> io::Result<()> {
+        if self.stopped {
+            return Ok(());
+        }
+        self.count += 1;
+        // clear the screen and move the cursor to the top left corner
+        write!(self.out, "\x1b[2J\x1b[H")?;
+        writeln!(self.out, "{}", frame.caption)?;
+        for row in &frame.cells {
+            let mut current = None;
+            for cell in row {
+                if current != Some((cell.fg, cell.bg)) {
+                    write!(
+                        self.out,
+                        "\x1b[{};{}m",
+                        30 + cell.fg.offset(),
+                        40 + cell.bg.offset()
+                    )?;
+                    current = Some((cell.fg, cell.bg));
+                }
+                write!(self.out, "{}", cell.symbol)?;
+            }
+            writeln!(self.out, "\x1b[0m")?;
+        }
+        if self.paused {
+            write!(
+                self.out,
+                "frame {} [Enter: step, c: continue, +/-: speed, q: quit] ",
+                self.count
+            )?;
+            self.out.flush()?;
+            self.command()
+        } else {
+            writeln!(self.out, "frame {}", self.count)?;
+            self.out.flush()?;
+            thread::sleep(self.delay);
+            Ok(())
+        }
+    }
+
+    /// Replay a sequence of recorded frames.
+    pub fn play(&mut self, frames: &[Frame]) -> io::Result<()> {
+        for frame in frames {
+            self.render(frame)?;
+        }
+        Ok(())
+    }
+
+    /// Read and execute commands until the user asks for the next frame.
+    fn command(&mut self) -> io::Result<()> {
+        loop {
+            let mut line = String::new();
+            if self.input.read_line(&mut line)? == 0 {
+                // no more commands, continue the playback without waiting
+                self.paused = false;
+                return Ok(());
+            }
+            match line.trim() {
+                "" => return Ok(()),
+                "c" => {
+                    self.paused = false;
+                    return Ok(());
+                }
+                "q" => {
+                    self.stopped = true;
+                    return Ok(());
+                }
+                "+" => self.delay /= 2,
+                "-" => self.delay = (self.delay * 2).max(Duration::from_millis(1)),
+                _ => {}
+            }
+        }
+    }
+}
+
+impl<W: Write, R: BufRead> FrameSink for Renderer<W, R> {
+    fn publish(&mut self, frame: Frame) {
+        self.render(&frame).expect("cannot write to the terminal")
+    }
+}
+
+#[test]
+fn test() {
+    let grid = vec![b"@.".to_vec(), b".@".to_vec()];
+    let frame = Frame::from_grid("test", &grid, |i, _j, &x| {
+        Cell::new(
+            x as char,
+            if i == 0 { Colour::Red } else { Colour::Default },
+        )
+    });
+    assert_eq!(frame.to_plain(), vec!["@.", ".@"]);
+
+    let mut out = Vec::new();
+    let mut renderer = Renderer::new(&mut out, "+\n\nq\n".as_bytes())
+        .with_delay(Duration::from_millis(4))
+        .stepping();
+    renderer
+        .play(&[frame.clone(), frame.clone(), frame])
+        .unwrap();
+    assert!(renderer.is_stopped());
+    assert_eq!(renderer.delay, Duration::from_millis(2));
+    assert_eq!(renderer.count, 2);
+    let out = String::from_utf8(out).unwrap();
+    assert!(out.contains("\x1b[31;49m@."));
+}